@@ -0,0 +1,96 @@
+//! Compare the number of allocations made by the standard decode path, `decode_into`, and `parse_slice_ref`.
+
+use nd_tokio_sse_codec::parse_slice_ref;
+use nd_tokio_sse_codec::SseCodec;
+use nd_tokio_sse_codec::SseEventBuffers;
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// An allocator that counts allocations.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const EVENTS: usize = 10_000;
+
+fn make_input() -> BytesMut {
+    let mut input = BytesMut::new();
+    for i in 0..EVENTS {
+        input.extend_from_slice(
+            format!("event: tick\nid: {i}\ndata: {{\"n\": {i}}}\n\n").as_bytes(),
+        );
+    }
+    input
+}
+
+/// Run a function, returning the number of allocations it made.
+fn count_allocations(f: impl FnOnce()) -> usize {
+    let start = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - start
+}
+
+fn main() {
+    let mut input = make_input();
+    let decode = count_allocations(|| {
+        let mut codec = SseCodec::new();
+        let mut n = 0;
+        while let Some(event) = codec.decode(&mut input).expect("failed to parse") {
+            n += event.data.map_or(0, |data| data.len());
+        }
+        std::hint::black_box(n);
+    });
+
+    let mut input = make_input();
+    let decode_into = count_allocations(|| {
+        let mut codec = SseCodec::new();
+        let mut out = SseEventBuffers::new();
+        let mut n = 0;
+        while codec
+            .decode_into(&mut input, &mut out)
+            .expect("failed to parse")
+        {
+            n += out.data().map_or(0, |data| data.len());
+        }
+        std::hint::black_box(n);
+    });
+
+    let input = make_input();
+    let parse_slice_ref = count_allocations(|| {
+        let mut n = 0;
+        for event in parse_slice_ref(&input) {
+            let event = event.expect("failed to parse");
+            n += event.data.map_or(0, |data| data.len());
+        }
+        std::hint::black_box(n);
+    });
+
+    println!("decoded {EVENTS} events");
+    println!("decode: {decode} allocations");
+    println!("decode_into: {decode_into} allocations");
+    println!("parse_slice_ref: {parse_slice_ref} allocations");
+}
@@ -0,0 +1,117 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use std::collections::HashSet;
+use syn::parse_macro_input;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Error;
+use syn::Fields;
+use syn::LitStr;
+use syn::Variant;
+
+/// Derive `FromSseEvent` for an enum.
+///
+/// Each variant is matched by its event type, set with `#[sse(event = "...")]`.
+/// By default, this is the name of the variant.
+/// Unit variants ignore the data, and variants with a single unnamed field parse it with `FromSseData`.
+#[proc_macro_derive(FromSseEvent, attributes(sse))]
+pub fn derive_from_sse_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Generate the impl.
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "FromSseEvent can only be derived for enums",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut event_types = HashSet::new();
+    let mut arms = Vec::with_capacity(data.variants.len());
+    for variant in data.variants.iter() {
+        let event_type = event_type(variant)?;
+        if !event_types.insert(event_type.value()) {
+            return Err(Error::new_spanned(
+                variant,
+                format!("duplicate event type \"{}\"", event_type.value()),
+            ));
+        }
+
+        let variant_ident = &variant.ident;
+        let value =
+            match &variant.fields {
+                Fields::Unit => quote!(#ident::#variant_ident),
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    let ty = &fields.unnamed[0].ty;
+                    quote! {
+                        #ident::#variant_ident(
+                            <#ty as ::nd_tokio_sse_codec::FromSseData>::from_sse_data(data).map_err(
+                                |source| ::nd_tokio_sse_codec::SseCodecError::InvalidEventData {
+                                    event: ::std::string::String::from(event_type),
+                                    source: ::std::convert::Into::into(source),
+                                },
+                            )?,
+                        )
+                    }
+                }
+                _ => return Err(Error::new_spanned(
+                    variant,
+                    "FromSseEvent variants must be unit variants or have a single unnamed field",
+                )),
+            };
+        arms.push(quote!(#event_type => ::std::result::Result::Ok(#value),));
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::nd_tokio_sse_codec::FromSseEvent for #ident #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn from_sse_event(
+                event: ::nd_tokio_sse_codec::SseEvent,
+            ) -> ::std::result::Result<Self, ::nd_tokio_sse_codec::SseCodecError> {
+                let event_type = event.event.as_deref().unwrap_or("message");
+                let data = event.data.as_deref().unwrap_or("");
+                match event_type {
+                    #(#arms)*
+                    _ => ::std::result::Result::Err(
+                        ::nd_tokio_sse_codec::SseCodecError::UnknownEvent {
+                            event: ::std::string::String::from(event_type),
+                        },
+                    ),
+                }
+            }
+        }
+    })
+}
+
+/// Get the event type of a variant.
+fn event_type(variant: &Variant) -> syn::Result<LitStr> {
+    let mut event_type = None;
+    for attr in variant.attrs.iter() {
+        if !attr.path().is_ident("sse") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("event") {
+                event_type = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported sse attribute"))
+            }
+        })?;
+    }
+
+    Ok(event_type.unwrap_or_else(|| LitStr::new(&variant.ident.to_string(), variant.ident.span())))
+}
@@ -0,0 +1,19 @@
+use nd_tokio_sse_codec::reqwest_sse;
+use tokio_stream::StreamExt;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let client = reqwest::Client::new();
+    let request = client
+        .get("https://sse.dev/test")
+        .build()
+        .expect("failed to build request");
+
+    // This will go on forever, reconnecting if the connection is lost...
+    let mut stream = std::pin::pin!(reqwest_sse(client, request));
+    while let Some(event) = stream.next().await {
+        let event = event.expect("invalid event");
+
+        println!("message: {}", event.data.expect("event had no message"));
+    }
+}
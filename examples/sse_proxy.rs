@@ -0,0 +1,21 @@
+use nd_tokio_sse_codec::SseEvent;
+use nd_tokio_sse_codec::SseProxy;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    // This could be the body of an upstream response.
+    let upstream = "id: upstream-a\ndata: hello\n\n: keep-alive\n\nid: upstream-b\ndata: world\n\n";
+
+    // Rewrite the upstream ids to a global sequence.
+    let mut sequence = 0_u64;
+    let mut proxy = SseProxy::new(|event: SseEvent| {
+        sequence += 1;
+        Some(event.with_id(sequence.to_string()))
+    });
+
+    let written = proxy
+        .run(upstream.as_bytes(), tokio::io::stdout())
+        .await
+        .expect("failed to proxy");
+    eprintln!("proxied {written} events");
+}
@@ -0,0 +1,32 @@
+use nd_tokio_sse_codec::reqwest_sse;
+use nd_tokio_sse_codec::SseEvent;
+use nd_tokio_sse_codec::TestSseServer;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let server = TestSseServer::bind().await.expect("failed to bind");
+    for i in 0..5 {
+        server.enqueue_after(
+            Duration::from_millis(500),
+            SseEvent::new()
+                .with_id(i.to_string())
+                .with_data(format!("tick {i}")),
+        );
+    }
+    let url = server.url();
+    server.close();
+
+    let client = reqwest::Client::new();
+    let request = client.get(url).build().expect("failed to build request");
+
+    // The stream would reconnect forever, so stop after the last event.
+    let stream = reqwest_sse(client, request).take(5);
+    let mut stream = std::pin::pin!(stream);
+    while let Some(event) = stream.next().await {
+        let event = event.expect("invalid event");
+
+        println!("message: {}", event.data.expect("event had no message"));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::split_field;
+use crate::trim_value;
+use crate::LineScanner;
+use crate::SseCodecError;
+use crate::SseEvent;
+use std::borrow::Cow;
+
+/// An sse event that borrows from its input
+///
+/// This avoids the global allocator for most events,
+/// which is useful for consumers that store events in a pool or arena.
+/// Such consumers can parse with [`parse_slice_ref`] and copy the borrowed values into their own storage.
+/// The data field is only owned if the event has multiple data lines, since they must be joined.
+#[derive(Debug, Default, PartialEq)]
+pub struct SseEventRef<'a> {
+    /// The event field
+    pub event: Option<&'a str>,
+
+    /// The data field
+    pub data: Option<Cow<'a, str>>,
+
+    /// The id field
+    pub id: Option<&'a str>,
+
+    /// The retry field
+    pub retry: Option<u64>,
+}
+
+impl SseEventRef<'_> {
+    /// Copy this event into an owned event.
+    pub fn into_owned(self) -> SseEvent {
+        SseEvent {
+            event: self.event.map(String::from),
+            data: self.data.map(Cow::into_owned),
+            id: self.id.map(String::from),
+            retry: self.retry,
+        }
+    }
+}
+
+/// Parse all events from a byte slice, borrowing from it where possible.
+///
+/// This is like [`parse_slice`](crate::parse_slice), but yields [`SseEventRef`]s.
+/// The end of the slice is treated as the end of the stream,
+/// so a trailing partial event is discarded.
+/// Iteration stops after the first error.
+pub fn parse_slice_ref(
+    input: &[u8],
+) -> impl Iterator<Item = Result<SseEventRef<'_>, SseCodecError>> + '_ {
+    let mut lines = LineScanner::default();
+    let mut position = 0;
+    let mut event = SseEventRef::default();
+    let mut done = false;
+
+    std::iter::from_fn(move || {
+        while !done {
+            let line_span = match lines.next_line(&input[position..]) {
+                Some(line_span) => line_span,
+                None => {
+                    done = true;
+                    break;
+                }
+            };
+            let bytes = &input[position..];
+            let line = match lines.line_str(&bytes[line_span.start..line_span.end]) {
+                Ok(line) => line,
+                Err(error) => {
+                    done = true;
+                    return Some(Err(error));
+                }
+            };
+            lines.consume(&line_span);
+            position += line_span.advance;
+
+            if line.is_empty() {
+                let event = std::mem::take(&mut event);
+
+                // If there is no data, don't dispatch, per spec.
+                if event.data.is_some() {
+                    return Some(Ok(event));
+                }
+
+                continue;
+            }
+
+            let (field, value) = match split_field(line) {
+                Some((field, value)) => (field, trim_value(value)),
+                None => continue,
+            };
+
+            match field {
+                "event" => event.event = Some(value),
+                "data" => match event.data.as_mut() {
+                    Some(data) => {
+                        // Join data lines with \n, per spec.
+                        let data = data.to_mut();
+                        data.push('\n');
+                        data.push_str(value);
+                    }
+                    None => event.data = Some(Cow::Borrowed(value)),
+                },
+                // Ignore if id has interior NULs, per spec.
+                "id" if !value.contains('\0') => event.id = Some(value),
+                "retry" => {
+                    // Ignore if not all ascii digits, per spec.
+                    if let Ok(value) = value.parse() {
+                        event.retry = Some(value);
+                    }
+                }
+                _ => {
+                    // Ignore other fields.
+                }
+            }
+        }
+
+        None
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_slice;
+
+    #[test]
+    fn parse_slice_ref_matches_parse_slice() {
+        let test_data = "event: a\ndata: 1\r\n\r\n: comment\rid: 2\rdata: 2\r\rdata: 3\ndata:\n\nretry: 5\n\nid: 3\nretry: 10\ndata: 4\n\ndata: 5";
+
+        let expected_events: Vec<_> = parse_slice(test_data.as_bytes())
+            .collect::<Result<_, _>>()
+            .expect("failed to parse");
+        let events: Vec<_> = parse_slice_ref(test_data.as_bytes())
+            .collect::<Result<_, _>>()
+            .expect("failed to parse");
+        assert!(events.len() == 4);
+        assert!(matches!(events[1].data, Some(Cow::Borrowed("2"))));
+        assert!(matches!(events[2].data, Some(Cow::Owned(_))));
+
+        let events: Vec<_> = events.into_iter().map(SseEventRef::into_owned).collect();
+        assert!(events == expected_events);
+
+        let mut iter = parse_slice_ref(b"data: a\n\ndata: \xFF\n\n");
+        assert!(iter.next().is_some_and(|event| event.is_ok()));
+        assert!(matches!(
+            iter.next(),
+            Some(Err(SseCodecError::InvalidUtf8 { line: 3, .. }))
+        ));
+        assert!(iter.next().is_none());
+    }
+}
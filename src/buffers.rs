@@ -0,0 +1,141 @@
+use crate::DecodeProgress;
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use tokio_util::bytes::BytesMut;
+
+/// Empty buffers that the codec can reuse for fields
+#[derive(Debug, Default)]
+pub(crate) struct SpareBuffers {
+    pub(crate) event: String,
+    pub(crate) data: String,
+    pub(crate) id: String,
+}
+
+/// Reusable buffers for the fields of a decoded event
+///
+/// See [`SseCodec::decode_into`].
+#[derive(Debug, Default)]
+pub struct SseEventBuffers {
+    event: Option<String>,
+    data: Option<String>,
+    id: Option<String>,
+    retry: Option<u64>,
+}
+
+impl SseEventBuffers {
+    /// Make new, empty buffers.
+    pub fn new() -> Self {
+        Self {
+            event: None,
+            data: None,
+            id: None,
+            retry: None,
+        }
+    }
+
+    /// The event field
+    pub fn event(&self) -> Option<&str> {
+        self.event.as_deref()
+    }
+
+    /// The data field
+    pub fn data(&self) -> Option<&str> {
+        self.data.as_deref()
+    }
+
+    /// The id field
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// The retry field
+    pub fn retry(&self) -> Option<u64> {
+        self.retry
+    }
+
+    /// Copy the fields into a new event.
+    pub fn to_event(&self) -> SseEvent {
+        SseEvent {
+            event: self.event.clone(),
+            data: self.data.clone(),
+            id: self.id.clone(),
+            retry: self.retry,
+        }
+    }
+}
+
+/// Store a new field value, returning the old buffer to the spare slot.
+fn store(slot: &mut Option<String>, value: Option<String>, spare: &mut String) {
+    if let Some(mut old) = std::mem::replace(slot, value) {
+        if old.capacity() > spare.capacity() {
+            old.clear();
+            *spare = old;
+        }
+    }
+}
+
+impl SseCodec {
+    /// Decode the next event into reusable buffers.
+    ///
+    /// Returns `true` if an event was decoded.
+    /// The buffers from the previous event are recycled by the codec,
+    /// so decoding many events does not allocate new strings once the buffers have grown.
+    /// This is a lower-level alternative to [`tokio_util::codec::Decoder::decode`].
+    pub fn decode_into(
+        &mut self,
+        bytes: &mut BytesMut,
+        out: &mut SseEventBuffers,
+    ) -> Result<bool, SseCodecError> {
+        loop {
+            match self.decode_line(bytes)? {
+                DecodeProgress::Field
+                | DecodeProgress::Comment
+                | DecodeProgress::SkippedInvalidUtf8
+                | DecodeProgress::BlankLine => {}
+                DecodeProgress::Event(event) => {
+                    store(&mut out.event, event.event, &mut self.parser.spare.event);
+                    store(&mut out.data, event.data, &mut self.parser.spare.data);
+                    store(&mut out.id, event.id, &mut self.parser.spare.id);
+                    out.retry = event.retry;
+
+                    return Ok(true);
+                }
+                DecodeProgress::Blocked => return Ok(false),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio_util::codec::Decoder;
+
+    #[test]
+    fn decode_into_matches_decode() {
+        let test_data =
+            "event: a\ndata: 1\nid: 1\n\ndata: 2\ndata: 3\n\nretry: 5\ndata: 4\n\nevent: b\ndata:\n\n";
+
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from(test_data);
+        let mut expected_events = Vec::new();
+        while let Some(event) = codec.decode(&mut buffer).expect("failed to parse") {
+            expected_events.push(event);
+        }
+
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from(test_data);
+        let mut out = SseEventBuffers::new();
+        let mut events = Vec::new();
+        while codec
+            .decode_into(&mut buffer, &mut out)
+            .expect("failed to parse")
+        {
+            events.push(out.to_event());
+        }
+
+        assert!(events.len() == 4);
+        assert!(events == expected_events);
+    }
+}
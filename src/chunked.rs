@@ -0,0 +1,279 @@
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use tokio_util::bytes::Buf;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// The state of the chunked transfer encoding parser
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChunkedState {
+    /// Reading a chunk size line.
+    Size,
+
+    /// Reading the data of a chunk, with the number of bytes left.
+    Data(usize),
+
+    /// Reading the newline after the data of a chunk.
+    DataEnd,
+
+    /// Reading the trailer lines after the last chunk.
+    Trailers,
+
+    /// The body is complete, so the rest of the bytes are skipped.
+    End,
+}
+
+/// An sse codec for bodies that still have HTTP chunked transfer encoding framing.
+///
+/// This is only needed by minimal clients that read responses from a raw socket.
+/// HTTP clients like reqwest remove the framing, so their bodies should be decoded with [`SseCodec`] directly.
+/// Chunk size lines, chunk extensions, and trailers are stripped before the SSE bytes are decoded.
+/// Make one with [`SseCodec::with_chunked_decoding`].
+#[derive(Debug)]
+pub struct ChunkedSseCodec {
+    /// The inner codec
+    codec: SseCodec,
+
+    /// The stripped SSE bytes
+    body: BytesMut,
+
+    /// The parser state
+    state: ChunkedState,
+
+    /// The number of bytes of the current framing line already scanned for a `\r\n`
+    scanned: usize,
+}
+
+impl SseCodec {
+    /// Wrap this codec, so that it strips HTTP chunked transfer encoding framing before decoding.
+    pub fn with_chunked_decoding(self) -> ChunkedSseCodec {
+        ChunkedSseCodec {
+            codec: self,
+            body: BytesMut::new(),
+            state: ChunkedState::Size,
+            scanned: 0,
+        }
+    }
+}
+
+impl ChunkedSseCodec {
+    /// Take the next line of the chunked framing, without its `\r\n`.
+    ///
+    /// Scanning resumes where the last call stopped, so a slowly arriving line is only scanned once.
+    fn next_line(&mut self, bytes: &mut BytesMut) -> Result<Option<BytesMut>, SseCodecError> {
+        // Back up one byte, in case the `\r\n` was split between reads.
+        let start = self.scanned.saturating_sub(1).min(bytes.len());
+        let newline_index = bytes[start..]
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .map(|index| start + index);
+
+        if let Some(limit) = self.codec.parser.config.max_line_length {
+            // A partial line counts towards the limit, so an endless line is caught too.
+            let line_length =
+                newline_index.unwrap_or_else(|| bytes.len() - usize::from(bytes.ends_with(b"\r")));
+            if line_length > limit {
+                return Err(SseCodecError::ChunkLineTooLong { limit });
+            }
+        }
+
+        let newline_index = match newline_index {
+            Some(newline_index) => newline_index,
+            None => {
+                self.scanned = bytes.len();
+                return Ok(None);
+            }
+        };
+        self.scanned = 0;
+        let line = bytes.split_to(newline_index);
+        bytes.advance(2);
+
+        Ok(Some(line))
+    }
+
+    /// Parse a chunk size line, skipping chunk extensions.
+    ///
+    /// Per RFC 9112, the size is one or more hex digits, optionally followed by whitespace and a chunk extension.
+    fn parse_chunk_size(line: &[u8]) -> Result<usize, SseCodecError> {
+        let digits = line.iter().take_while(|b| b.is_ascii_hexdigit()).count();
+        let (size, rest) = line.split_at(digits);
+        if size.is_empty() || !(rest.is_empty() || rest.trim_ascii_start().starts_with(b";")) {
+            return Err(SseCodecError::InvalidChunkSize);
+        }
+
+        std::str::from_utf8(size)
+            .ok()
+            .and_then(|size| usize::from_str_radix(size, 16).ok())
+            .ok_or(SseCodecError::InvalidChunkSize)
+    }
+}
+
+impl Decoder for ChunkedSseCodec {
+    type Item = SseEvent;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            if let Some(event) = self.codec.decode(&mut self.body)? {
+                return Ok(Some(event));
+            }
+
+            match self.state {
+                ChunkedState::Size => {
+                    let line = match self.next_line(bytes)? {
+                        Some(line) => line,
+                        None => return Ok(None),
+                    };
+                    let size = Self::parse_chunk_size(&line)?;
+
+                    self.state = if size == 0 {
+                        ChunkedState::Trailers
+                    } else {
+                        ChunkedState::Data(size)
+                    };
+                }
+                ChunkedState::Data(remaining) => {
+                    if bytes.is_empty() {
+                        return Ok(None);
+                    }
+
+                    let n = remaining.min(bytes.len());
+                    self.body.extend_from_slice(&bytes.split_to(n));
+                    self.state = if n == remaining {
+                        ChunkedState::DataEnd
+                    } else {
+                        ChunkedState::Data(remaining - n)
+                    };
+                }
+                ChunkedState::DataEnd => {
+                    if bytes.len() < 2 {
+                        return Ok(None);
+                    }
+                    if !bytes.starts_with(b"\r\n") {
+                        return Err(SseCodecError::MissingChunkTerminator);
+                    }
+                    bytes.advance(2);
+                    self.state = ChunkedState::Size;
+                }
+                ChunkedState::Trailers => {
+                    let line = match self.next_line(bytes)? {
+                        Some(line) => line,
+                        None => return Ok(None),
+                    };
+
+                    if line.is_empty() {
+                        self.state = ChunkedState::End;
+                    }
+                }
+                ChunkedState::End => {
+                    bytes.clear();
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(event) = self.decode(bytes)? {
+            return Ok(Some(event));
+        }
+
+        self.codec.decode_eof(&mut self.body)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn chunked() {
+        let test_data = concat!(
+            "7\r\ndata: a\r\n",
+            "3;name=value\r\n\n\nd\r\n",
+            "a\r\nata: split\r\n",
+            "4\r\n\n\n\n\n\r\n",
+            "0\r\nTrailer: x\r\n\r\n",
+            "ignored",
+        );
+        for chunk_size in [1, 3, test_data.len()] {
+            let mut codec = SseCodec::new().with_chunked_decoding();
+            let mut buffer = BytesMut::new();
+            let mut events = Vec::new();
+            for chunk in test_data.as_bytes().chunks(chunk_size) {
+                buffer.extend_from_slice(chunk);
+                while let Some(event) = codec.decode(&mut buffer).expect("failed to parse") {
+                    events.push(event.data);
+                }
+            }
+            while let Some(event) = codec.decode_eof(&mut buffer).expect("failed to parse") {
+                events.push(event.data);
+            }
+            assert!(events == [Some("a".into()), Some("split".into())]);
+        }
+    }
+
+    #[test]
+    fn invalid_chunk_size() {
+        let mut buffer = BytesMut::from("data: a\r\n\r\n");
+        let error = SseCodec::new()
+            .with_chunked_decoding()
+            .decode(&mut buffer)
+            .expect_err("missing error");
+        assert!(matches!(error, SseCodecError::InvalidChunkSize));
+    }
+
+    #[test]
+    fn invalid_chunk_size_syntax() {
+        for size in ["+7", " 7", "7 ", "7 x", ";name", "1x"] {
+            let mut buffer = BytesMut::from(format!("{size}\r\ndata: a\r\n").as_str());
+            let error = SseCodec::new()
+                .with_chunked_decoding()
+                .decode(&mut buffer)
+                .expect_err("missing error");
+            assert!(matches!(error, SseCodecError::InvalidChunkSize));
+        }
+
+        let mut buffer = BytesMut::from("9 ;name=value\r\ndata: a\n\n\r\n");
+        let event = SseCodec::new()
+            .with_chunked_decoding()
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("a"));
+    }
+
+    #[test]
+    fn missing_chunk_terminator() {
+        let mut buffer = BytesMut::from("7\r\ndata: a\n\n\r\n");
+        let error = SseCodec::new()
+            .with_chunked_decoding()
+            .decode(&mut buffer)
+            .expect_err("missing error");
+        assert!(matches!(error, SseCodecError::MissingChunkTerminator));
+    }
+
+    #[test]
+    fn chunk_line_too_long() {
+        let mut codec = SseCodec::new()
+            .with_max_line_length(4)
+            .with_chunked_decoding();
+        let mut buffer = BytesMut::from("0000");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        buffer.extend_from_slice(b"\r");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        buffer.extend_from_slice(b"0");
+        let error = codec.decode(&mut buffer).expect_err("missing error");
+        assert!(matches!(
+            error,
+            SseCodecError::ChunkLineTooLong { limit: 4 }
+        ));
+    }
+}
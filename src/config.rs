@@ -0,0 +1,136 @@
+use crate::BlankLinePolicy;
+use crate::EventSeparator;
+use crate::SseCodec;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// The configuration of an [`SseCodec`]
+///
+/// This holds the options set with the `with_*` methods of the codec and its builder,
+/// except callbacks and stats.
+/// Get it with [`SseCodec::config`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct SseCodecConfig {
+    /// Per-field overrides for trimming the leading space of a value.
+    pub field_trim: HashMap<String, bool>,
+
+    /// Alternate names for fields.
+    pub field_aliases: HashMap<String, String>,
+
+    /// The maximum length of a line, in bytes.
+    pub max_line_length: Option<usize>,
+
+    /// The maximum number of bytes consumed from a stream.
+    pub max_stream_bytes: Option<usize>,
+
+    /// Whether to skip lines that are not valid utf8, instead of erroring.
+    pub utf8_recovery: bool,
+
+    /// Whether values made only of spaces are kept as-is, instead of trimming the leading space.
+    pub preserve_trailing_space: bool,
+
+    /// Whether a partial event is dispatched at the end of the stream, instead of being discarded.
+    pub eof_flush: bool,
+
+    /// The maximum number of lines consumed by a single call to decode.
+    pub max_lines_per_poll: Option<usize>,
+
+    /// Whether discarding a partial event at the end of the stream is an error.
+    pub eof_error: bool,
+
+    /// The line that dispatches an event.
+    pub event_separator: EventSeparator,
+
+    /// How a separator is handled when the event has no data.
+    pub blank_line_policy: BlankLinePolicy,
+
+    /// The maximum number of ids kept in the id history.
+    pub id_history_capacity: usize,
+
+    /// The reconnect delay used until the server sends a retry field.
+    pub default_reconnect_delay: Duration,
+
+    /// The minimum reconnect delay, or `None` to only limit the delay after a retry of 0.
+    pub min_reconnect_delay: Option<Duration>,
+
+    /// The maximum reconnect delay.
+    pub max_reconnect_delay: Option<Duration>,
+}
+
+/// The reconnect delay after a retry of 0, if there is no minimum reconnect delay.
+pub(crate) const ZERO_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+impl Default for SseCodecConfig {
+    fn default() -> Self {
+        Self {
+            field_trim: HashMap::new(),
+            field_aliases: HashMap::new(),
+            max_line_length: None,
+            max_stream_bytes: None,
+            utf8_recovery: false,
+            preserve_trailing_space: false,
+            eof_flush: false,
+            max_lines_per_poll: None,
+            eof_error: false,
+            event_separator: EventSeparator::BlankLine,
+            blank_line_policy: BlankLinePolicy::Spec,
+            id_history_capacity: 0,
+            default_reconnect_delay: Duration::from_secs(3),
+            min_reconnect_delay: None,
+            max_reconnect_delay: None,
+        }
+    }
+}
+
+impl SseCodec {
+    /// Get the configuration of this codec.
+    ///
+    /// This can be logged or compared, to reproduce the behavior of a codec.
+    pub fn config(&self) -> &SseCodecConfig {
+        &self.parser.config
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn config() {
+        assert!(*SseCodec::new().config() == SseCodecConfig::default());
+
+        let codec = SseCodec::builder()
+            .max_line_length(1024)
+            .utf8_recovery(true)
+            .event_separator(EventSeparator::Line(";".into()))
+            .blank_line_policy(BlankLinePolicy::Legacy)
+            .build();
+        let config = codec.config();
+        assert!(config.max_line_length == Some(1024));
+        assert!(config.utf8_recovery);
+        assert!(!config.eof_flush);
+        assert!(config.event_separator == EventSeparator::Line(";".into()));
+        assert!(config.blank_line_policy == BlankLinePolicy::Legacy);
+
+        // Continuations keep the configuration.
+        assert!(codec.new_with_continuation().config() == config);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn serialize() {
+        let codec = SseCodec::builder()
+            .max_line_length(1024)
+            .event_separator(EventSeparator::Line(";".into()))
+            .blank_line_policy(BlankLinePolicy::Legacy)
+            .build();
+        let value = serde_json::to_value(codec.config()).expect("failed to serialize");
+        assert!(value["max_line_length"] == 1024);
+        assert!(value["utf8_recovery"] == false);
+        assert!(value["event_separator"] == serde_json::json!({ "Line": ";" }));
+        assert!(value["blank_line_policy"] == "Legacy");
+        assert!(value["max_reconnect_delay"].is_null());
+    }
+}
@@ -0,0 +1,312 @@
+use crate::split_field;
+use crate::trim_value;
+use crate::LineEnding;
+use crate::LineScanner;
+use crate::SseCodecError;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio_util::bytes::Buf;
+use tokio_util::bytes::BytesMut;
+
+/// The kind of a [`ConformanceIssue`]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ConformanceIssueKind {
+    /// A line was not valid utf8.
+    InvalidUtf8,
+
+    /// An id field contained a NUL, so clients ignore it.
+    NulInId,
+
+    /// A retry field was not made of only ASCII digits, so clients ignore it.
+    InvalidRetry {
+        /// The value of the field.
+        value: String,
+    },
+
+    /// A field name is not defined by the spec, so clients ignore it.
+    ///
+    /// Field names are case-sensitive, and a space before the colon is part of the name.
+    UnknownField {
+        /// The name of the field.
+        field: String,
+    },
+
+    /// A line ended with a different kind of newline than the first line.
+    MixedLineEndings {
+        /// The newline of the first line.
+        expected: LineEnding,
+
+        /// The newline of this line.
+        found: LineEnding,
+    },
+
+    /// An event had an event field but no data field, so clients do not dispatch it.
+    EventWithoutData,
+
+    /// The stream ended in the middle of a line or event, so clients discard it.
+    UnterminatedEvent,
+}
+
+/// A spec violation found by a [`SseConformanceChecker`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConformanceIssue {
+    /// The 1-based number of the line in the stream.
+    pub line: u64,
+
+    /// The kind of issue.
+    pub kind: ConformanceIssueKind,
+}
+
+/// A checker that reports every spec violation in a stream, for testing servers.
+///
+/// Unlike decoding, a violation does not stop the check.
+/// All issues are collected and returned by [`SseConformanceChecker::finish`].
+#[derive(Debug, Default)]
+pub struct SseConformanceChecker {
+    /// The line scanner
+    lines: LineScanner,
+
+    /// The bytes not checked yet.
+    buffer: BytesMut,
+
+    /// The number of lines checked so far.
+    line: u64,
+
+    /// The newline of the first line.
+    line_ending: Option<LineEnding>,
+
+    /// The line of the first field of the current event.
+    event_start: Option<u64>,
+
+    /// The line of the event field of the current event.
+    event_field: Option<u64>,
+
+    /// Whether the current event has a data field.
+    has_data: bool,
+
+    /// The issues found so far.
+    issues: Vec<ConformanceIssue>,
+}
+
+impl SseConformanceChecker {
+    /// Make a new conformance checker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check a complete stream.
+    pub fn check(bytes: &[u8]) -> Vec<ConformanceIssue> {
+        let mut checker = Self::new();
+        checker.push(bytes);
+        checker.finish()
+    }
+
+    /// Read and check a stream until eof.
+    pub async fn check_reader<R>(mut reader: R) -> Result<Vec<ConformanceIssue>, SseCodecError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut checker = Self::new();
+        let mut buffer = [0; 8192];
+        loop {
+            let n = reader.read(&mut buffer).await?;
+            if n == 0 {
+                break;
+            }
+            checker.push(&buffer[..n]);
+        }
+
+        Ok(checker.finish())
+    }
+
+    /// Check the next bytes of the stream.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+        self.check_lines(false);
+    }
+
+    /// End the stream, returning all issues found.
+    pub fn finish(mut self) -> Vec<ConformanceIssue> {
+        self.check_lines(true);
+
+        if !self.buffer.is_empty() {
+            self.report(self.line + 1, ConformanceIssueKind::UnterminatedEvent);
+        } else if let Some(line) = self.event_start {
+            self.report(line, ConformanceIssueKind::UnterminatedEvent);
+        }
+
+        self.issues
+    }
+
+    /// Check all complete lines in the buffer.
+    fn check_lines(&mut self, eof: bool) {
+        while let Some(line) = self.lines.next_line(&self.buffer) {
+            // Wait to see if a trailing \r is part of a \r\n.
+            if line.pending_cr && !eof {
+                break;
+            }
+
+            let line_ending = line.line_ending(&self.buffer).unwrap_or(LineEnding::Cr);
+            let bytes = self.buffer[line.start..line.end].to_vec();
+            self.lines.consume(&line);
+            self.buffer.advance(line.advance);
+            self.line += 1;
+
+            match self.line_ending {
+                None => self.line_ending = Some(line_ending),
+                Some(expected) if expected != line_ending => self.report(
+                    self.line,
+                    ConformanceIssueKind::MixedLineEndings {
+                        expected,
+                        found: line_ending,
+                    },
+                ),
+                Some(_) => {}
+            }
+
+            self.check_line(&bytes);
+        }
+    }
+
+    /// Check a single line, without its newline.
+    fn check_line(&mut self, bytes: &[u8]) {
+        if bytes.is_empty() {
+            if let (Some(line), false) = (self.event_field, self.has_data) {
+                self.report(line, ConformanceIssueKind::EventWithoutData);
+            }
+            self.event_start = None;
+            self.event_field = None;
+            self.has_data = false;
+            return;
+        }
+
+        let line = match std::str::from_utf8(bytes) {
+            Ok(line) => line,
+            Err(_) => {
+                self.event_start.get_or_insert(self.line);
+                self.report(self.line, ConformanceIssueKind::InvalidUtf8);
+                return;
+            }
+        };
+
+        let (field, value) = match split_field(line) {
+            Some(field) => field,
+            None => return,
+        };
+        self.event_start.get_or_insert(self.line);
+        let value = trim_value(value);
+
+        match field {
+            "event" => self.event_field = Some(self.line),
+            "data" => self.has_data = true,
+            "id" if value.contains('\0') => self.report(self.line, ConformanceIssueKind::NulInId),
+            "id" => {}
+            "retry" if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) => self
+                .report(
+                    self.line,
+                    ConformanceIssueKind::InvalidRetry {
+                        value: value.to_string(),
+                    },
+                ),
+            "retry" => {}
+            field => self.report(
+                self.line,
+                ConformanceIssueKind::UnknownField {
+                    field: field.to_string(),
+                },
+            ),
+        }
+    }
+
+    /// Record an issue.
+    fn report(&mut self, line: u64, kind: ConformanceIssueKind) {
+        self.issues.push(ConformanceIssue { line, kind });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_DATA: &[u8] = concat!(
+        ": ok\n",
+        "event: a\n",
+        "data: 1\n",
+        "\n",
+        "Data: 2\n",
+        "event: b\n",
+        "\n",
+        "id: 1\0\n",
+        "retry: 10s\n",
+        "data : 3\r\n",
+        "\n",
+        "data: 4",
+    )
+    .as_bytes();
+
+    fn expected_issues() -> Vec<ConformanceIssue> {
+        vec![
+            ConformanceIssue {
+                line: 5,
+                kind: ConformanceIssueKind::UnknownField {
+                    field: "Data".into(),
+                },
+            },
+            ConformanceIssue {
+                line: 6,
+                kind: ConformanceIssueKind::EventWithoutData,
+            },
+            ConformanceIssue {
+                line: 8,
+                kind: ConformanceIssueKind::NulInId,
+            },
+            ConformanceIssue {
+                line: 9,
+                kind: ConformanceIssueKind::InvalidRetry {
+                    value: "10s".into(),
+                },
+            },
+            ConformanceIssue {
+                line: 10,
+                kind: ConformanceIssueKind::MixedLineEndings {
+                    expected: LineEnding::Lf,
+                    found: LineEnding::CrLf,
+                },
+            },
+            ConformanceIssue {
+                line: 10,
+                kind: ConformanceIssueKind::UnknownField {
+                    field: "data ".into(),
+                },
+            },
+            ConformanceIssue {
+                line: 12,
+                kind: ConformanceIssueKind::UnterminatedEvent,
+            },
+        ]
+    }
+
+    #[test]
+    fn conformance() {
+        assert!(SseConformanceChecker::check(TEST_DATA) == expected_issues());
+        assert!(SseConformanceChecker::check(b"event: a\ndata: 1\n\nretry: 5\n\n").is_empty());
+    }
+
+    #[test]
+    fn conformance_split_reads() {
+        let mut checker = SseConformanceChecker::new();
+        for b in TEST_DATA {
+            checker.push(&[*b]);
+        }
+        assert!(checker.finish() == expected_issues());
+    }
+
+    #[tokio::test]
+    async fn conformance_reader() {
+        let issues = SseConformanceChecker::check_reader(TEST_DATA)
+            .await
+            .expect("failed to read");
+        assert!(issues == expected_issues());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::DecodeProgress;
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseStreamItem;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// An sse codec that yields each data line as soon as it is parsed.
+///
+/// Each event is yielded as a [`SseStreamItem::DataChunk`] for each data line,
+/// followed by a [`SseStreamItem::EventEnd`] once the event is complete.
+/// This allows processing a huge event before all of it arrives, without buffering its data.
+/// If the stream ends in the middle of an event, its chunks are not followed by an end marker.
+/// Make one with [`SseCodec::with_data_chunks`].
+#[derive(Debug, Default)]
+pub struct DataChunkSseCodec {
+    codec: SseCodec,
+}
+
+impl SseCodec {
+    /// Wrap this codec, so that it yields data lines as they are parsed, instead of whole events.
+    pub fn with_data_chunks(self) -> DataChunkSseCodec {
+        DataChunkSseCodec { codec: self }
+    }
+}
+
+impl DataChunkSseCodec {
+    /// Take the data line that was just parsed, if any.
+    fn take_chunk(&mut self) -> Option<SseStreamItem> {
+        // The data buffer is cleared after each line, but left as Some so the codec still dispatches the event.
+        let data = self
+            .codec
+            .parser
+            .data
+            .as_mut()
+            .filter(|data| !data.is_empty())?;
+        let chunk = data.strip_suffix('\n').unwrap_or(data).to_string();
+        data.clear();
+
+        Some(SseStreamItem::DataChunk {
+            event: self.codec.parser.event.clone(),
+            chunk,
+        })
+    }
+}
+
+impl Decoder for DataChunkSseCodec {
+    type Item = SseStreamItem;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            match self.codec.decode_line(bytes)? {
+                DecodeProgress::Field => {
+                    if let Some(chunk) = self.take_chunk() {
+                        return Ok(Some(chunk));
+                    }
+                }
+                DecodeProgress::Comment
+                | DecodeProgress::SkippedInvalidUtf8
+                | DecodeProgress::BlankLine => {}
+                DecodeProgress::Event(mut event) => {
+                    event.data = None;
+                    return Ok(Some(SseStreamItem::EventEnd(event)));
+                }
+                DecodeProgress::Blocked => return Ok(None),
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(item) = self.decode(bytes)? {
+            return Ok(Some(item));
+        }
+
+        // Complete a partial line here, so a partial data line is still yielded as a chunk.
+        if self.codec.parser.config.eof_flush && !bytes.is_empty() {
+            bytes.extend_from_slice(b"\n");
+            if let Some(item) = self.decode(bytes)? {
+                return Ok(Some(item));
+            }
+        }
+
+        let event = self.codec.decode_eof(bytes)?;
+        Ok(event.map(|mut event| {
+            event.data = None;
+            SseStreamItem::EventEnd(event)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SseEvent;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    #[tokio::test]
+    async fn data_chunks() {
+        let test_data =
+            "event: big\ndata: line 1\ndata:\ndata: line 3\nid: 1\n\ndata: small\n\ndata: partial\n";
+        let items: Vec<_> =
+            FramedRead::new(test_data.as_bytes(), SseCodec::new().with_data_chunks())
+                .map(|item| item.expect("failed to parse"))
+                .collect()
+                .await;
+
+        let chunk = |event: Option<&str>, chunk: &str| SseStreamItem::DataChunk {
+            event: event.map(String::from),
+            chunk: chunk.into(),
+        };
+        let expected_items = [
+            chunk(Some("big"), "line 1"),
+            chunk(Some("big"), ""),
+            chunk(Some("big"), "line 3"),
+            SseStreamItem::EventEnd(SseEvent::new().with_event("big").with_id("1")),
+            chunk(None, "small"),
+            SseStreamItem::EventEnd(SseEvent::new()),
+            chunk(None, "partial"),
+        ];
+        assert!(items == expected_items);
+    }
+}
@@ -0,0 +1,301 @@
+use crate::LineEnding;
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use tokio_util::bytes::BufMut;
+use tokio_util::bytes::Bytes;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Encoder;
+
+/// Check if a value contains a newline.
+fn has_newline(value: &str) -> bool {
+    value.bytes().any(|b| b == b'\r' || b == b'\n')
+}
+
+/// Write a single field line.
+fn put_field(dst: &mut BytesMut, line_ending: LineEnding, field: &str, value: &str) {
+    let line_ending = line_ending.as_bytes();
+    dst.reserve(field.len() + value.len() + 2 + line_ending.len());
+    dst.put_slice(field.as_bytes());
+    dst.put_slice(b": ");
+    dst.put_slice(value.as_bytes());
+    dst.put_slice(line_ending);
+}
+
+/// Write an event in the wire format.
+pub(crate) fn encode_event(
+    event: &SseEvent,
+    line_ending: LineEnding,
+    dst: &mut BytesMut,
+) -> Result<(), SseCodecError> {
+    // Validate first, so that nothing is written for an invalid event.
+    if event.event.as_deref().is_some_and(has_newline) {
+        return Err(SseCodecError::NewlineInField { field: "event" });
+    }
+    if event.id.as_deref().is_some_and(has_newline) {
+        return Err(SseCodecError::NewlineInField { field: "id" });
+    }
+
+    if let Some(value) = event.event.as_deref() {
+        put_field(dst, line_ending, "event", value);
+    }
+
+    if let Some(data) = event.data.as_deref() {
+        // Split on all 3 kinds of newlines, so each line becomes a data field.
+        let mut rest = data;
+        loop {
+            match rest.find(['\r', '\n']) {
+                Some(index) => {
+                    put_field(dst, line_ending, "data", &rest[..index]);
+                    let newline_len = if rest[index..].starts_with("\r\n") {
+                        2
+                    } else {
+                        1
+                    };
+                    rest = &rest[index + newline_len..];
+                }
+                None => {
+                    put_field(dst, line_ending, "data", rest);
+                    break;
+                }
+            }
+        }
+    }
+
+    if let Some(value) = event.id.as_deref() {
+        put_field(dst, line_ending, "id", value);
+    }
+
+    if let Some(value) = event.retry {
+        put_field(dst, line_ending, "retry", &value.to_string());
+    }
+
+    dst.put_slice(line_ending.as_bytes());
+
+    Ok(())
+}
+
+impl SseEvent {
+    /// Encode this event in the wire format, using `\n` line endings.
+    ///
+    /// This is useful for building a response body without an [`Encoder`].
+    pub fn to_bytes(&self) -> Result<Bytes, SseCodecError> {
+        let mut dst = BytesMut::new();
+        encode_event(self, LineEnding::Lf, &mut dst)?;
+        Ok(dst.freeze())
+    }
+}
+
+impl Encoder<&SseEvent> for SseCodec {
+    type Error = SseCodecError;
+
+    fn encode(&mut self, event: &SseEvent, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        encode_event(event, LineEnding::Lf, dst)
+    }
+}
+
+impl Encoder<SseEvent> for SseCodec {
+    type Error = SseCodecError;
+
+    fn encode(&mut self, event: SseEvent, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.encode(&event, dst)
+    }
+}
+
+/// An sse encoder with a configurable line ending
+///
+/// [`SseCodec`] also implements [`Encoder`], but always writes `\n`.
+/// Use this for legacy clients that need `\r\n` or `\r`.
+#[derive(Debug, Clone, Default)]
+pub struct SseEncoder {
+    line_ending: LineEnding,
+}
+
+impl SseEncoder {
+    /// Make a new encoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the newline written after each line.
+    ///
+    /// This is used for both field lines and the blank line that ends an event.
+    /// By default, this is [`LineEnding::Lf`].
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+}
+
+impl Encoder<&SseEvent> for SseEncoder {
+    type Error = SseCodecError;
+
+    fn encode(&mut self, event: &SseEvent, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        encode_event(event, self.line_ending, dst)
+    }
+}
+
+impl Encoder<SseEvent> for SseEncoder {
+    type Error = SseCodecError;
+
+    fn encode(&mut self, event: SseEvent, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.encode(&event, dst)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio_util::codec::Decoder;
+
+    #[test]
+    fn encode() {
+        let event = SseEvent::new()
+            .with_event("test")
+            .with_data("hello")
+            .with_id("1")
+            .with_retry(1000);
+
+        let mut buffer = BytesMut::new();
+        SseCodec::new()
+            .encode(&event, &mut buffer)
+            .expect("failed to encode");
+        assert!(&buffer[..] == b"event: test\ndata: hello\nid: 1\nretry: 1000\n\n");
+    }
+
+    #[test]
+    fn multiline_data_round_trip() {
+        let event = SseEvent::new().with_data("line 1\n line 2\n\nline 4\n");
+
+        let mut buffer = BytesMut::new();
+        let mut codec = SseCodec::new();
+        codec.encode(&event, &mut buffer).expect("failed to encode");
+        assert!(&buffer[..] == b"data: line 1\ndata:  line 2\ndata: \ndata: line 4\ndata: \n\n");
+
+        let decoded = codec
+            .decode(&mut buffer)
+            .expect("failed to decode")
+            .expect("missing event");
+        assert!(decoded == event);
+    }
+
+    #[test]
+    fn data_multiline() {
+        // Each kind of newline splits the data into the same lines.
+        for data in ["a\nb\n\nc", "a\r\nb\r\n\r\nc", "a\rb\r\rc", "a\r\nb\r\rc"] {
+            let event = SseEvent::data_multiline(data);
+            assert!(event.data.as_deref() == Some("a\nb\n\nc"));
+
+            let mut buffer = BytesMut::new();
+            let mut codec = SseCodec::new();
+            codec.encode(&event, &mut buffer).expect("failed to encode");
+            assert!(&buffer[..] == b"data: a\ndata: b\ndata: \ndata: c\n\n");
+
+            let decoded = codec
+                .decode(&mut buffer)
+                .expect("failed to parse")
+                .expect("missing event");
+            assert!(decoded == event);
+        }
+    }
+
+    #[test]
+    fn newline_in_field() {
+        let event = SseEvent::new().with_event("a\nb");
+
+        let mut buffer = BytesMut::new();
+        let error = SseCodec::new()
+            .encode(&event, &mut buffer)
+            .expect_err("missing error");
+        assert!(matches!(
+            error,
+            SseCodecError::NewlineInField { field: "event" }
+        ));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn line_ending_round_trip() {
+        let event = SseEvent::new()
+            .with_event("test")
+            .with_data("a\nb")
+            .with_id("1")
+            .with_retry(10);
+
+        let expected = [
+            (
+                LineEnding::Lf,
+                "event: test\ndata: a\ndata: b\nid: 1\nretry: 10\n\n",
+            ),
+            (
+                LineEnding::CrLf,
+                "event: test\r\ndata: a\r\ndata: b\r\nid: 1\r\nretry: 10\r\n\r\n",
+            ),
+            (
+                LineEnding::Cr,
+                "event: test\rdata: a\rdata: b\rid: 1\rretry: 10\r\r",
+            ),
+        ];
+        for (line_ending, expected) in expected {
+            let mut encoder = SseEncoder::new().with_line_ending(line_ending);
+            let mut buffer = BytesMut::new();
+            encoder
+                .encode(&event, &mut buffer)
+                .expect("failed to encode");
+            assert!(&buffer[..] == expected.as_bytes());
+
+            let decoded = SseCodec::new()
+                .decode(&mut buffer)
+                .expect("failed to decode")
+                .expect("missing event");
+            assert!(decoded == event);
+        }
+    }
+
+    #[test]
+    fn retry_round_trip() {
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::new();
+        codec
+            .encode(
+                &SseEvent::new().with_data("a").with_retry(5000),
+                &mut buffer,
+            )
+            .expect("failed to encode");
+        assert!(&buffer[..] == b"data: a\nretry: 5000\n\n");
+
+        let decoded = codec
+            .decode(&mut buffer)
+            .expect("failed to decode")
+            .expect("missing event");
+        assert!(decoded.retry == Some(5000));
+
+        // A standalone directive is not dispatched, but still updates the reconnection time.
+        codec
+            .encode(SseEvent::retry_directive(250), &mut buffer)
+            .expect("failed to encode");
+        assert!(&buffer[..] == b"retry: 250\n\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to decode")
+            .is_none());
+        assert!(codec.last_retry() == Some(250));
+    }
+
+    #[test]
+    fn to_bytes() {
+        let event = SseEvent::new()
+            .with_event("test")
+            .with_data("a\nb")
+            .with_id("1");
+
+        let bytes = event.to_bytes().expect("failed to encode");
+        assert!(&bytes[..] == b"event: test\ndata: a\ndata: b\nid: 1\n\n");
+
+        let decoded = SseCodec::new()
+            .decode(&mut BytesMut::from(&bytes[..]))
+            .expect("failed to decode")
+            .expect("missing event");
+        assert!(decoded == event);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::split_field;
+use crate::trim_value;
+use crate::LineScanner;
+use crate::SseCodecError;
+use tokio_util::bytes::Buf;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// An sse field
+#[derive(Debug, PartialEq)]
+pub struct SseField {
+    /// The field name
+    pub name: String,
+
+    /// The field value
+    pub value: String,
+}
+
+/// An item yielded by the field codec
+#[derive(Debug, PartialEq)]
+pub enum SseFieldItem {
+    /// A field line.
+    Field(SseField),
+
+    /// A blank line, which dispatches the event.
+    Dispatch,
+}
+
+/// An sse codec that yields individual fields instead of buffering events.
+///
+/// Comments are skipped.
+/// Field values are not interpreted, so `retry` and `id` values are passed through as-is.
+#[derive(Debug, Default)]
+pub struct SseFieldCodec {
+    /// The line scanner
+    lines: LineScanner,
+}
+
+impl SseFieldCodec {
+    /// Make a new SSE Field decoder.
+    pub fn new() -> Self {
+        Self {
+            lines: LineScanner::default(),
+        }
+    }
+}
+
+impl Decoder for SseFieldCodec {
+    type Item = SseFieldItem;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            let line_span = match self.lines.next_line(bytes) {
+                Some(line_span) => line_span,
+                None => {
+                    return Ok(None);
+                }
+            };
+
+            let line = self
+                .lines
+                .line_str(&bytes[line_span.start..line_span.end])?;
+
+            if line.is_empty() {
+                self.lines.consume(&line_span);
+                bytes.advance(line_span.advance);
+                return Ok(Some(SseFieldItem::Dispatch));
+            }
+
+            let item = split_field(line).map(|(name, value)| {
+                SseFieldItem::Field(SseField {
+                    name: name.into(),
+                    value: trim_value(value).into(),
+                })
+            });
+
+            self.lines.consume(&line_span);
+            bytes.advance(line_span.advance);
+
+            if let Some(item) = item {
+                return Ok(Some(item));
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // Unterminated lines are discarded, just like in the event codec.
+        let item = self.decode(buf)?;
+        if item.is_none() {
+            self.lines.discard_partial();
+        }
+
+        Ok(item)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    fn field(name: &str, value: &str) -> SseFieldItem {
+        SseFieldItem::Field(SseField {
+            name: name.into(),
+            value: value.into(),
+        })
+    }
+
+    #[tokio::test]
+    async fn fields_in_order() {
+        let test_data = "event: test\r\n: comment\ndata: a\rdata:b\nid\n\ndata: c\n\ndata: d";
+        let mut reader = FramedRead::new(test_data.as_bytes(), SseFieldCodec::new());
+
+        let mut items = Vec::new();
+        while let Some(item) = reader.next().await {
+            items.push(item.expect("failed to parse"));
+        }
+
+        let expected_items = [
+            field("event", "test"),
+            field("data", "a"),
+            field("data", "b"),
+            field("id", ""),
+            SseFieldItem::Dispatch,
+            field("data", "c"),
+            SseFieldItem::Dispatch,
+        ];
+        assert!(items == expected_items);
+    }
+}
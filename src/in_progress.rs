@@ -0,0 +1,105 @@
+use crate::SseCodec;
+
+/// A borrowed view of the event a codec is currently decoding
+///
+/// Make one with [`SseCodec::in_progress`].
+#[derive(Debug, Default, PartialEq)]
+pub struct InProgressView<'a> {
+    /// The event field received so far
+    pub event: Option<&'a str>,
+
+    /// The id field received so far
+    pub id: Option<&'a str>,
+
+    /// The retry field received so far
+    pub retry: Option<u64>,
+
+    /// The length of the data received so far, in bytes, or `None` if there was no data field.
+    ///
+    /// This includes the newlines between data lines.
+    pub data_len: Option<usize>,
+
+    /// The data received so far, or `None` if there was no data field.
+    ///
+    /// Like a dispatched event, the newline after the last data line is not included.
+    pub data: Option<&'a str>,
+
+    /// The number of bytes consumed for this event, including field names and newlines.
+    pub bytes: usize,
+}
+
+impl InProgressView<'_> {
+    /// Check if no lines of the event have been received.
+    pub fn is_empty(&self) -> bool {
+        self.bytes == 0
+    }
+}
+
+impl SseCodec {
+    /// Get a view of the partially decoded event.
+    ///
+    /// This borrows the fields of the codec, and does not allocate,
+    /// so it is cheap enough to call on every update of a progress display.
+    /// Lines that are not complete yet are not included.
+    pub fn in_progress(&self) -> InProgressView<'_> {
+        InProgressView {
+            event: self.parser.event.as_deref(),
+            id: self.parser.id.as_deref(),
+            retry: self.parser.retry,
+            // Each data line is followed by a \n, which is trimmed from the last line when the event is dispatched.
+            data_len: self
+                .parser
+                .data
+                .as_ref()
+                .map(|data| data.len().saturating_sub(1)),
+            data: self
+                .parser
+                .data
+                .as_deref()
+                .map(|data| data.strip_suffix('\n').unwrap_or(data)),
+            bytes: self.parser.event_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio_util::bytes::BytesMut;
+    use tokio_util::codec::Decoder;
+
+    #[test]
+    fn in_progress() {
+        let mut codec = SseCodec::new();
+        assert!(codec.in_progress().is_empty());
+
+        let mut buffer = BytesMut::from("event: big\nid: 7\ndata: abc\ndata: de");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+
+        let view = codec.in_progress();
+        assert!(view.event == Some("big"));
+        assert!(view.id == Some("7"));
+        assert!(view.retry.is_none());
+        assert!(view.data_len == Some(3));
+        assert!(view.bytes == 27);
+
+        buffer.extend_from_slice(b"\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(codec.in_progress().data_len == Some(6));
+        assert!(codec.in_progress().data == Some("abc\nde"));
+
+        buffer.extend_from_slice(b"\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.map(|data| data.len()) == Some(6));
+        assert!(codec.in_progress() == InProgressView::default());
+    }
+}
@@ -0,0 +1,254 @@
+use std::future::Future;
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio::io::ReadBuf;
+use tokio::time::Sleep;
+
+/// A reader that treats `WouldBlock` errors as "no data yet".
+///
+/// [`FramedRead`](tokio_util::codec::FramedRead) treats every IO error as fatal,
+/// so a reader that spuriously returns `WouldBlock` would end the stream.
+/// Well-behaved tokio readers return `Poll::Pending` instead,
+/// but custom adapters over channels or blocking transports may not.
+///
+/// When the inner reader returns `WouldBlock`, this wakes the task and returns `Poll::Pending`,
+/// so the read is retried on the next poll.
+#[derive(Debug)]
+pub struct RetryWouldBlock<R> {
+    reader: R,
+}
+
+impl<R> RetryWouldBlock<R> {
+    /// Wrap a reader.
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    /// Get the inner reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R> AsyncRead for RetryWouldBlock<R>
+where
+    R: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match Pin::new(&mut self.reader).poll_read(cx, buf) {
+            Poll::Ready(Err(error)) if error.kind() == ErrorKind::WouldBlock => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            poll => poll,
+        }
+    }
+}
+
+/// The IO error returned by [`ReadTimeout`], which is converted to [`SseCodecError::ReadTimeout`](crate::SseCodecError::ReadTimeout).
+#[derive(Debug)]
+struct ReadTimeoutError {
+    timeout: Duration,
+}
+
+impl std::fmt::Display for ReadTimeoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a read did not complete within {:?}", self.timeout)
+    }
+}
+
+impl std::error::Error for ReadTimeoutError {}
+
+/// Get the timeout of an IO error returned by [`ReadTimeout`].
+pub(crate) fn read_timeout(error: &std::io::Error) -> Option<Duration> {
+    if error.kind() != ErrorKind::TimedOut {
+        return None;
+    }
+
+    error
+        .get_ref()
+        .and_then(|error| error.downcast_ref::<ReadTimeoutError>())
+        .map(|error| error.timeout)
+}
+
+/// A reader that fails if a single read takes too long.
+///
+/// This detects connections that stall in the middle of a line,
+/// which an idle timeout between events would not catch if bytes keep trickling in.
+/// The timer starts when a read returns `Poll::Pending`, and stops when it completes.
+/// With a [`FramedRead`](tokio_util::codec::FramedRead), a timeout produces [`SseCodecError::ReadTimeout`](crate::SseCodecError::ReadTimeout).
+#[derive(Debug)]
+pub struct ReadTimeout<R> {
+    reader: R,
+    timeout: Duration,
+
+    /// The timer of the current read.
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<R> ReadTimeout<R> {
+    /// Wrap a reader.
+    pub fn new(reader: R, timeout: Duration) -> Self {
+        Self {
+            reader,
+            timeout,
+            sleep: None,
+        }
+    }
+
+    /// Get the inner reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Wrap a reader, so that it fails if a single read takes longer than `timeout`.
+///
+/// See [`ReadTimeout`].
+pub fn with_read_timeout<R>(reader: R, timeout: Duration) -> ReadTimeout<R> {
+    ReadTimeout::new(reader, timeout)
+}
+
+impl<R> AsyncRead for ReadTimeout<R>
+where
+    R: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+
+        if let Poll::Ready(result) = Pin::new(&mut this.reader).poll_read(cx, buf) {
+            this.sleep = None;
+            return Poll::Ready(result);
+        }
+
+        let timeout = this.timeout;
+        let sleep = this
+            .sleep
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+        if sleep.as_mut().poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+        this.sleep = None;
+
+        Poll::Ready(Err(std::io::Error::new(
+            ErrorKind::TimedOut,
+            ReadTimeoutError { timeout },
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SseCodec;
+    use std::collections::VecDeque;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    /// A reader that returns each chunk in order, with `None` meaning `WouldBlock`.
+    struct ChunkReader {
+        chunks: VecDeque<Option<&'static [u8]>>,
+    }
+
+    impl AsyncRead for ChunkReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            match self.chunks.pop_front() {
+                Some(Some(chunk)) => {
+                    buf.put_slice(chunk);
+                    Poll::Ready(Ok(()))
+                }
+                Some(None) => Poll::Ready(Err(ErrorKind::WouldBlock.into())),
+                None => Poll::Ready(Ok(())),
+            }
+        }
+    }
+
+    fn chunk_reader() -> ChunkReader {
+        ChunkReader {
+            chunks: VecDeque::from([
+                None,
+                Some(&b"data: a\n"[..]),
+                None,
+                None,
+                Some(&b"\ndata: b\n\n"[..]),
+            ]),
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_would_block() {
+        let reader = RetryWouldBlock::new(chunk_reader());
+        let events: Vec<_> = FramedRead::new(reader, SseCodec::new())
+            .map(|event| event.expect("failed to parse").data)
+            .collect()
+            .await;
+        assert!(events == [Some("a".into()), Some("b".into())]);
+    }
+
+    #[tokio::test]
+    async fn would_block_is_fatal_without_wrapper() {
+        let mut reader = FramedRead::new(chunk_reader(), SseCodec::new());
+        let error = reader
+            .next()
+            .await
+            .expect("missing item")
+            .expect_err("missing error");
+        assert!(matches!(
+            error,
+            crate::SseCodecError::Io(error) if error.kind() == ErrorKind::WouldBlock
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn read_timeout() {
+        use tokio::io::AsyncWriteExt;
+
+        let (mut writer, reader) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            // Each byte arrives within the timeout, so the slow event is still decoded.
+            for byte in b"data: a\n\n" {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                writer.write_all(&[*byte]).await.expect("failed to write");
+            }
+
+            writer.write_all(b"data: b").await.expect("failed to write");
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            writer.write_all(b"\n\n").await.expect("failed to write");
+        });
+
+        let reader = with_read_timeout(reader, Duration::from_secs(1));
+        let mut reader = FramedRead::new(reader, SseCodec::new());
+        let event = reader
+            .next()
+            .await
+            .expect("missing event")
+            .expect("failed to parse");
+        assert!(event.data.as_deref() == Some("a"));
+
+        let error = reader
+            .next()
+            .await
+            .expect("missing item")
+            .expect_err("missing error");
+        assert!(matches!(
+            error,
+            crate::SseCodecError::ReadTimeout { timeout } if timeout == Duration::from_secs(1)
+        ));
+    }
+}
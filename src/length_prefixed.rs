@@ -0,0 +1,127 @@
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use tokio_util::bytes::Buf;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// An sse codec for streams where each event block is prefixed with its length.
+///
+/// This is not part of the SSE spec.
+/// Each block is preceded by a header line like `#123\n`,
+/// where `123` is the length of the block in bytes.
+/// The framing is stripped and the blocks are decoded as a single SSE stream.
+#[derive(Debug, Default)]
+pub struct LengthPrefixedSseCodec {
+    /// The inner codec
+    codec: SseCodec,
+
+    /// The stripped SSE bytes
+    body: BytesMut,
+
+    /// The number of bytes left in the current block.
+    remaining: usize,
+}
+
+impl LengthPrefixedSseCodec {
+    /// Make a new length-prefixed SSE Event decoder.
+    pub fn new() -> Self {
+        Self::with_codec(SseCodec::new())
+    }
+
+    /// Make a new length-prefixed SSE Event decoder, using the given codec for the blocks.
+    pub fn with_codec(codec: SseCodec) -> Self {
+        Self {
+            codec,
+            body: BytesMut::new(),
+            remaining: 0,
+        }
+    }
+}
+
+impl Decoder for LengthPrefixedSseCodec {
+    type Item = SseEvent;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            if let Some(event) = self.codec.decode(&mut self.body)? {
+                return Ok(Some(event));
+            }
+
+            if self.remaining == 0 {
+                let newline_index = match bytes.iter().position(|b| *b == b'\n') {
+                    Some(newline_index) => newline_index,
+                    None => return Ok(None),
+                };
+
+                let header = &bytes[..newline_index];
+                let header = header.strip_suffix(b"\r").unwrap_or(header);
+                self.remaining = header
+                    .strip_prefix(b"#")
+                    .filter(|length| !length.is_empty() && length.iter().all(u8::is_ascii_digit))
+                    .and_then(|length| std::str::from_utf8(length).ok()?.parse().ok())
+                    .ok_or(SseCodecError::InvalidLengthPrefix)?;
+
+                bytes.advance(newline_index + 1);
+                continue;
+            }
+
+            let n = std::cmp::min(self.remaining, bytes.len());
+            if n == 0 {
+                return Ok(None);
+            }
+            self.body.extend_from_slice(&bytes.split_to(n));
+            self.remaining -= n;
+        }
+    }
+
+    fn decode_eof(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(bytes)? {
+            Some(frame) => Ok(Some(frame)),
+            None => self.codec.decode_eof(&mut self.body),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    #[tokio::test]
+    async fn length_prefixed() {
+        let test_data = "#27\nevent: a\ndata: #3\ndata: b\n\n#9\ndata: c\n\n#8\ndata: d\n";
+        let mut reader = FramedRead::new(test_data.as_bytes(), LengthPrefixedSseCodec::new());
+
+        let mut events = Vec::new();
+        while let Some(event) = reader.next().await {
+            events.push(event.expect("failed to parse"));
+        }
+
+        let expected_events = [
+            SseEvent {
+                event: Some("a".into()),
+                data: Some("#3\nb".into()),
+                id: None,
+                retry: None,
+            },
+            SseEvent {
+                event: None,
+                data: Some("c".into()),
+                id: None,
+                retry: None,
+            },
+        ];
+        assert!(events == expected_events);
+    }
+
+    #[test]
+    fn invalid_length_prefix() {
+        let mut codec = LengthPrefixedSseCodec::new();
+        let mut buffer = BytesMut::from("data: a\n\n");
+        let error = codec.decode(&mut buffer).expect_err("missing error");
+        assert!(matches!(error, SseCodecError::InvalidLengthPrefix));
+    }
+}
@@ -1,22 +1,302 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
 use tokio_util::bytes::Buf;
+use tokio_util::bytes::Bytes;
 use tokio_util::bytes::BytesMut;
 use tokio_util::codec::Decoder;
 
+#[cfg(feature = "decoder")]
+mod borrowed;
+mod buffers;
+#[cfg(feature = "decoder")]
+mod chunked;
+// The config and stats are part of the parser state, so they are always available.
+mod config;
+#[cfg(feature = "test-util")]
+mod conformance;
+#[cfg(feature = "decoder")]
+mod data_chunks;
+#[cfg(feature = "encoder")]
+mod encoder;
+#[cfg(feature = "decoder")]
+mod field;
+#[cfg(feature = "decoder")]
+mod in_progress;
+#[cfg(feature = "io")]
+mod io;
+#[cfg(feature = "decoder")]
+mod length_prefixed;
+#[cfg(feature = "decoder")]
+mod lossy;
+#[cfg(feature = "decoder")]
+mod middleware;
+#[cfg(feature = "decoder")]
+mod multipart;
+#[cfg(feature = "decoder")]
+mod open_signal;
+mod parser;
+#[cfg(all(feature = "encoder", feature = "io"))]
+mod proxy;
+#[cfg(feature = "reconnect")]
+mod reconnect;
+#[cfg(feature = "record")]
+mod record;
+#[cfg(feature = "reqwest")]
+mod reqwest_client;
+#[cfg(feature = "decoder")]
+mod retry_update;
+#[cfg(all(feature = "decoder", feature = "io", feature = "stream"))]
+mod spill;
+mod stats;
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(feature = "sync")]
+mod sync;
+#[cfg(feature = "test-util")]
+mod test_util;
+#[cfg(feature = "time")]
+mod timestamped;
+#[cfg(feature = "decoder")]
+mod transaction;
+#[cfg(feature = "decoder")]
+mod typed;
+#[cfg(all(feature = "encoder", feature = "io"))]
+mod writer;
+
+#[cfg(feature = "decoder")]
+pub use self::borrowed::parse_slice_ref;
+#[cfg(feature = "decoder")]
+pub use self::borrowed::SseEventRef;
+use self::buffers::SpareBuffers;
+pub use self::buffers::SseEventBuffers;
+#[cfg(feature = "decoder")]
+pub use self::chunked::ChunkedSseCodec;
+pub use self::config::SseCodecConfig;
+#[cfg(feature = "test-util")]
+pub use self::conformance::ConformanceIssue;
+#[cfg(feature = "test-util")]
+pub use self::conformance::ConformanceIssueKind;
+#[cfg(feature = "test-util")]
+pub use self::conformance::SseConformanceChecker;
+#[cfg(feature = "decoder")]
+pub use self::data_chunks::DataChunkSseCodec;
+#[cfg(feature = "encoder")]
+pub use self::encoder::SseEncoder;
+#[cfg(feature = "decoder")]
+pub use self::field::SseField;
+#[cfg(feature = "decoder")]
+pub use self::field::SseFieldCodec;
+#[cfg(feature = "decoder")]
+pub use self::field::SseFieldItem;
+#[cfg(feature = "decoder")]
+pub use self::in_progress::InProgressView;
+#[cfg(feature = "io")]
+pub use self::io::with_read_timeout;
+#[cfg(feature = "io")]
+pub use self::io::ReadTimeout;
+#[cfg(feature = "io")]
+pub use self::io::RetryWouldBlock;
+#[cfg(feature = "decoder")]
+pub use self::length_prefixed::LengthPrefixedSseCodec;
+#[cfg(feature = "decoder")]
+pub use self::lossy::LossyDataSseCodec;
+#[cfg(feature = "decoder")]
+pub use self::lossy::SseBytesEvent;
+#[cfg(feature = "decoder")]
+pub use self::middleware::SseMiddleware;
+#[cfg(feature = "decoder")]
+pub use self::middleware::StripId;
+#[cfg(feature = "decoder")]
+pub use self::multipart::MultipartSseCodec;
+#[cfg(feature = "decoder")]
+pub use self::open_signal::OpenSignalSseCodec;
+#[cfg(feature = "decoder")]
+pub use self::open_signal::SseStreamItem;
+pub use self::parser::SseParser;
+#[cfg(all(feature = "encoder", feature = "io"))]
+pub use self::proxy::SseProxy;
+#[cfg(feature = "reconnect")]
+pub use self::reconnect::ReconnectPolicy;
+#[cfg(feature = "reconnect")]
+pub use self::reconnect::SseReconnect;
+#[cfg(feature = "record")]
+pub use self::record::SseRecorder;
+#[cfg(feature = "record")]
+pub use self::record::SseRecording;
+#[cfg(feature = "record")]
+pub use self::record::SseReplayer;
+#[cfg(feature = "reqwest")]
+pub use self::reqwest_client::reqwest_sse;
+#[cfg(feature = "decoder")]
+pub use self::retry_update::RetryUpdateSseCodec;
+#[cfg(all(feature = "decoder", feature = "io", feature = "stream"))]
+pub use self::spill::SpillFrame;
+#[cfg(all(feature = "decoder", feature = "io", feature = "stream"))]
+pub use self::spill::SpillItem;
+#[cfg(all(feature = "decoder", feature = "io", feature = "stream"))]
+pub use self::spill::SpillingSseCodec;
+use self::stats::SseStats;
+pub use self::stats::SseStatsHandle;
+#[cfg(feature = "test-util")]
+pub use self::test_util::TestSseServer;
+#[cfg(feature = "time")]
+pub use self::timestamped::TimestampedEvent;
+#[cfg(feature = "time")]
+pub use self::timestamped::TimestampedSseCodec;
+#[cfg(feature = "decoder")]
+pub use self::transaction::Transaction;
+#[cfg(feature = "decoder")]
+pub use self::typed::FromSseData;
+#[cfg(feature = "decoder")]
+pub use self::typed::FromSseEvent;
+#[cfg(all(feature = "decoder", feature = "json"))]
+pub use self::typed::Json;
+#[cfg(feature = "derive")]
+pub use nd_tokio_sse_codec_derive::FromSseEvent;
+
+// Let derived impls refer to this crate by name in its own tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as nd_tokio_sse_codec;
+#[cfg(all(feature = "encoder", feature = "io"))]
+pub use self::writer::SseWriter;
+
 /// An sse codec error
 #[derive(Debug)]
 pub enum SseCodecError {
     /// A line was not valid utf8.
-    InvalidUtf8(std::str::Utf8Error),
+    InvalidUtf8 {
+        /// The 1-based number of the line in the stream.
+        line: u64,
+
+        /// The utf8 error.
+        source: std::str::Utf8Error,
+    },
 
     /// An IO error occurred.
     Io(std::io::Error),
+
+    /// A line was longer than the configured limit.
+    LineTooLong {
+        /// The 1-based number of the line in the stream.
+        line: u64,
+
+        /// The maximum line length, in bytes.
+        limit: usize,
+    },
+
+    /// A length prefix header was invalid.
+    InvalidLengthPrefix,
+
+    /// The chunked transfer encoding framing was invalid.
+    InvalidChunkSize,
+
+    /// The data of a chunk was not followed by a `\r\n`.
+    MissingChunkTerminator,
+
+    /// A chunk size or trailer line was longer than the configured limit.
+    ChunkLineTooLong {
+        /// The maximum line length, in bytes.
+        limit: usize,
+    },
+
+    /// A field value that cannot span multiple lines contained a newline.
+    NewlineInField {
+        /// The name of the field.
+        field: &'static str,
+    },
+
+    /// The server responded with a status other than 200.
+    InvalidStatus(u16),
+
+    /// The server responded with a content type other than `text/event-stream`.
+    InvalidContentType(Option<String>),
+
+    /// A request could not be cloned to reconnect.
+    UncloneableRequest,
+
+    /// The stream ended with a partial event, which was discarded.
+    UnterminatedEvent {
+        /// The number of bytes of the partial event.
+        bytes_discarded: usize,
+    },
+
+    /// A string that should contain exactly one event contained a different number of events.
+    NotSingleEvent {
+        /// The number of events.
+        count: usize,
+    },
+
+    /// An event had an event type that could not be converted.
+    UnknownEvent {
+        /// The event type.
+        event: String,
+    },
+
+    /// The data of an event could not be converted.
+    InvalidEventData {
+        /// The event type.
+        event: String,
+
+        /// The parse error.
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// The stream was longer than the configured limit.
+    StreamTooLarge {
+        /// The maximum length of the stream, in bytes.
+        limit: usize,
+    },
+
+    /// A single read of the underlying reader took longer than the configured timeout.
+    ReadTimeout {
+        /// The read timeout.
+        timeout: std::time::Duration,
+    },
 }
 
 impl std::fmt::Display for SseCodecError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Self::InvalidUtf8(_) => write!(f, "a line was not valid utf8"),
+            Self::InvalidUtf8 { line, .. } => write!(f, "line {line} was not valid utf8"),
             Self::Io(_) => write!(f, "an I/O error occured"),
+            Self::LineTooLong { line, limit } => {
+                write!(f, "line {line} was longer than {limit} bytes")
+            }
+            Self::InvalidLengthPrefix => write!(f, "a length prefix was invalid"),
+            Self::InvalidChunkSize => write!(f, "the chunked transfer encoding was invalid"),
+            Self::MissingChunkTerminator => write!(f, "a chunk was not terminated by a newline"),
+            Self::ChunkLineTooLong { limit } => {
+                write!(f, "a chunked framing line was longer than {limit} bytes")
+            }
+            Self::NewlineInField { field } => {
+                write!(f, "the \"{field}\" field contained a newline")
+            }
+            Self::InvalidStatus(status) => write!(f, "invalid http status {status}"),
+            Self::InvalidContentType(Some(content_type)) => {
+                write!(f, "invalid content type \"{content_type}\"")
+            }
+            Self::InvalidContentType(None) => write!(f, "missing content type"),
+            Self::UncloneableRequest => write!(f, "the request could not be cloned"),
+            Self::UnterminatedEvent { bytes_discarded } => write!(
+                f,
+                "the stream ended with a partial event, discarding {bytes_discarded} bytes"
+            ),
+            Self::NotSingleEvent { count } => {
+                write!(f, "expected a single event, but found {count}")
+            }
+            Self::UnknownEvent { event } => write!(f, "unknown event type \"{event}\""),
+            Self::InvalidEventData { event, .. } => {
+                write!(f, "the data of a \"{event}\" event was invalid")
+            }
+            Self::StreamTooLarge { limit } => {
+                write!(f, "the stream was longer than {limit} bytes")
+            }
+            Self::ReadTimeout { timeout } => {
+                write!(f, "a read did not complete within {timeout:?}")
+            }
         }
     }
 }
@@ -24,20 +304,40 @@ impl std::fmt::Display for SseCodecError {
 impl std::error::Error for SseCodecError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::InvalidUtf8(error) => Some(error),
+            Self::InvalidUtf8 { source, .. } => Some(source),
             Self::Io(error) => Some(error),
+            Self::LineTooLong { .. } => None,
+            Self::InvalidLengthPrefix => None,
+            Self::InvalidChunkSize => None,
+            Self::MissingChunkTerminator => None,
+            Self::ChunkLineTooLong { .. } => None,
+            Self::NewlineInField { .. } => None,
+            Self::InvalidStatus(_) => None,
+            Self::InvalidContentType(_) => None,
+            Self::UncloneableRequest => None,
+            Self::UnterminatedEvent { .. } => None,
+            Self::NotSingleEvent { .. } => None,
+            Self::UnknownEvent { .. } => None,
+            Self::InvalidEventData { source, .. } => Some(&**source),
+            Self::StreamTooLarge { .. } => None,
+            Self::ReadTimeout { .. } => None,
         }
     }
 }
 
 impl From<std::io::Error> for SseCodecError {
     fn from(error: std::io::Error) -> Self {
+        #[cfg(feature = "io")]
+        if let Some(timeout) = self::io::read_timeout(&error) {
+            return Self::ReadTimeout { timeout };
+        }
+
         Self::Io(error)
     }
 }
 
 /// An sse event
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 pub struct SseEvent {
     /// The event field
     pub event: Option<String>,
@@ -52,315 +352,2987 @@ pub struct SseEvent {
     pub retry: Option<u64>,
 }
 
-/// An sse codec
-#[derive(Debug)]
-pub struct SseCodec {
-    // Check if the last newline was a \r.
-    last_newline_cr: bool,
-
-    /// The event field
-    event: Option<String>,
-
-    /// The data field
-    data: Option<String>,
-
-    /// The id field
-    id: Option<String>,
-
-    /// The retry field
-    retry: Option<u64>,
-}
-
-impl SseCodec {
-    /// Make a new SSE Event decoder.
+impl SseEvent {
+    /// Make a new event with no fields set.
     pub fn new() -> Self {
         Self {
-            last_newline_cr: false,
             event: None,
             data: None,
             id: None,
             retry: None,
         }
     }
-}
-
-impl Decoder for SseCodec {
-    type Item = SseEvent;
-    type Error = SseCodecError;
-
-    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        loop {
-            // We need at least 1 byte to work with.
-            if bytes.is_empty() {
-                return Ok(None);
-            }
-
-            // Need to handle: \n, \r\n, \r
-            // If the last newline was \r, trim the \n if one occurs.
-            if self.last_newline_cr && bytes[0] == b'\n' {
-                bytes.advance(1);
-                self.last_newline_cr = false;
-            }
-
-            let newline_index = match bytes.iter().position(|b| *b == b'\r' || *b == b'\n') {
-                Some(newline_index) => {
-                    // To handle a multi-byte newline,
-                    // we need to discard the next byte if the current newline is a \r and the next byte is a \n.
-                    // However, doing that here will lead to issues if a \r newline is the last newline in a stream.
-                    // Instead, set a flag and skip the extra \n then if needed.
-                    if bytes[newline_index] == b'\r' {
-                        self.last_newline_cr = true;
-                    }
-
-                    newline_index
-                }
-                None => {
-                    return Ok(None);
-                }
-            };
 
-            let line =
-                std::str::from_utf8(&bytes[..newline_index]).map_err(SseCodecError::InvalidUtf8)?;
-            let advance = line.len() + 1;
+    /// Set the event field.
+    pub fn with_event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
 
-            if line.is_empty() {
-                bytes.advance(advance);
+    /// Set the data field.
+    ///
+    /// The data may contain newlines.
+    /// When encoded, each line is written as a separate `data:` line,
+    /// which are joined back together with `\n` when decoded.
+    pub fn with_data(mut self, data: impl Into<String>) -> Self {
+        self.data = Some(data.into());
+        self
+    }
 
-                if let Some(data) = self.data.as_mut() {
-                    // Trim trailing \n, per-spec.
-                    if data.ends_with('\n') {
-                        data.pop();
-                    }
-                }
+    /// Make an event with data made of multiple lines.
+    ///
+    /// The data is split on `\n`, `\r\n` and `\r`, and each line is written as a separate `data:` line when encoded.
+    /// Newlines are normalized to `\n`, which is how decoding joins the lines,
+    /// so the event is unchanged by an encode and decode round trip.
+    pub fn data_multiline(data: impl Into<String>) -> Self {
+        let mut data = data.into();
+        if data.contains('\r') {
+            data = data.replace("\r\n", "\n").replace('\r', "\n");
+        }
 
-                return Ok(Some(SseEvent {
-                    event: self.event.take(),
-                    data: self.data.take(),
-                    id: self.id.take(),
-                    retry: self.retry.take(),
-                }));
-            }
+        Self::new().with_data(data)
+    }
 
-            let colon_index = line.bytes().position(|b| b == b':');
+    /// Set the id field.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
 
-            let (field, value) = match colon_index {
-                Some(0) => {
-                    // TODO: Consider letting user know about comments
-                    bytes.advance(advance);
-                    continue;
-                }
-                Some(index) => {
-                    let (field, mut value) = line.split_at(index);
-                    // Trim the :
-                    value = &value[1..];
-
-                    // If it has a starting space, trim that.
-                    if value.as_bytes().first() == Some(&b' ') {
-                        value = &value[1..];
-                    }
+    /// Set the retry field.
+    ///
+    /// The retry field sets the client's reconnection time, in milliseconds.
+    pub fn with_retry(mut self, retry: u64) -> Self {
+        self.retry = Some(retry);
+        self
+    }
 
-                    (field, value)
-                }
-                None => (line, ""),
-            };
+    /// Make an event with only the retry field set.
+    ///
+    /// When encoded, this is a standalone `retry:` block.
+    /// Clients update their reconnection time, but don't dispatch an event, since there is no data.
+    pub fn retry_directive(retry: u64) -> Self {
+        Self::new().with_retry(retry)
+    }
 
-            match field {
-                "event" => {
-                    // Overwrite old buffer, per spec.
-                    self.event = Some(value.into());
-                }
+    /// Make an event from field and value pairs, with the same semantics as decoding.
+    ///
+    /// Data values are joined with newlines, while later event and id values overwrite earlier ones.
+    /// Ids with a NUL, retry values that are not integers, and unknown fields are ignored.
+    /// Values are used as-is, without trimming a leading space.
+    pub fn from_fields<I>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut event = Self::new();
+        for (field, value) in fields {
+            match field.as_str() {
+                "event" => event.event = Some(value),
                 "data" => {
-                    // Append to data buffer and append \n, per spec.
-                    let data = self.data.get_or_insert_with(String::new);
-                    data.push_str(value);
+                    let data = event.data.get_or_insert_with(String::new);
+                    data.push_str(&value);
                     data.push('\n');
                 }
-                "id" => {
-                    // Ignore if id has interior NULs, per spec.
-                    if !value.contains('\0') {
-                        self.id = Some(value.into());
-                    }
-                }
+                "id" if !value.contains('\0') => event.id = Some(value),
                 "retry" => {
-                    // Ignore if not all ascii digits, per spec.
-                    // Also, attempt to parse into usable integer format,
-                    // which is implementation-defined by the spec,
-                    // as long as it can hold a few seconds in milliseconds.
                     if let Ok(value) = value.parse() {
-                        self.retry = Some(value);
+                        event.retry = Some(value);
                     }
                 }
-                _ => {
-                    // Ignore other fields.
-                }
+                _ => {}
             }
+        }
 
-            bytes.advance(advance);
+        // Trim trailing \n, per-spec.
+        if let Some(data) = event.data.as_mut() {
+            data.pop();
         }
+
+        event
     }
+}
 
-    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match self.decode(buf)? {
-            Some(frame) => Ok(Some(frame)),
-            None => {
-                // Decode will only return None if it is passed an empty buffer or not have a trailing newline.
-                // Per-spec, buffered event parts should be discarded if the stream is terminated without a trailing newline.
-                Ok(None)
-            }
+impl TryFrom<&str> for SseEvent {
+    type Error = SseCodecError;
+
+    /// Parse a single event block.
+    ///
+    /// The final blank line may be omitted.
+    /// Returns an error if the block does not contain exactly one event.
+    fn try_from(block: &str) -> Result<Self, Self::Error> {
+        let mut codec = SseCodec::new().with_eof_flush(true);
+        let mut buffer = BytesMut::from(block);
+
+        let mut events = Vec::with_capacity(1);
+        while let Some(event) = codec.decode_eof(&mut buffer)? {
+            events.push(event);
+        }
+
+        if events.len() != 1 {
+            return Err(SseCodecError::NotSingleEvent {
+                count: events.len(),
+            });
         }
+
+        Ok(events.swap_remove(0))
     }
 }
 
-impl Default for SseCodec {
-    fn default() -> Self {
-        Self::new()
+#[cfg(feature = "base64")]
+impl SseEvent {
+    /// Decode the data field as standard base64.
+    ///
+    /// Newlines are ignored, so the base64 may be split over multiple data lines.
+    /// An event with no data field decodes to no bytes.
+    pub fn data_base64(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        use base64::Engine;
+
+        let data = self.data.as_deref().unwrap_or_default();
+        let data: std::borrow::Cow<str> = if data.contains('\n') {
+            data.split('\n').collect::<String>().into()
+        } else {
+            data.into()
+        };
+
+        base64::engine::general_purpose::STANDARD.decode(data.as_bytes())
     }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use tokio_stream::StreamExt;
-    use tokio_util::codec::FramedRead;
+#[cfg(feature = "json")]
+impl SseEvent {
+    /// Parse the data field as json.
+    ///
+    /// Returns an error if there is no data field.
+    pub fn data_json<T>(&self) -> Result<T, serde_json::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let data = self
+            .data
+            .as_deref()
+            .ok_or_else(|| serde::de::Error::custom("the event has no data field"))?;
 
-    #[tokio::test]
-    async fn corpus() {
-        let mut dir_iter = tokio::fs::read_dir("corpus")
-            .await
-            .expect("failed to iter dir");
+        serde_json::from_str(data)
+    }
+}
 
-        while let Some(entry) = dir_iter
-            .next_entry()
-            .await
-            .expect("failed to read next entry")
-        {
-            let test_data = tokio::fs::read_to_string(entry.path())
-                .await
-                .expect("failed to read test data");
-            let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+/// The kind of progress made by decoding a single line
+#[derive(Debug, PartialEq)]
+pub enum DecodeProgress {
+    /// A field line was consumed.
+    Field,
 
-            while let Some(event) = reader.next().await {
-                let event = event.expect("failed to parse");
-                dbg!(event);
-            }
+    /// A comment line was consumed.
+    Comment,
+
+    /// A line that was not valid utf8 was skipped.
+    SkippedInvalidUtf8,
+
+    /// A blank line was consumed, completing an event.
+    Event(SseEvent),
+
+    /// A blank line was consumed, but no event was dispatched because there was no data.
+    BlankLine,
+
+    /// There is no complete line in the buffer, so more data is needed.
+    Blocked,
+}
+
+/// The line that dispatches an event
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum EventSeparator {
+    /// A blank line, per spec.
+    #[default]
+    BlankLine,
+
+    /// A line with exactly this content, for non-standard streams.
+    ///
+    /// Blank lines are ignored when using a custom separator.
+    Line(String),
+}
+
+/// How a separator is handled when the event has no data
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BlankLinePolicy {
+    /// Don't dispatch an event, per spec.
+    #[default]
+    Spec,
+
+    /// Dispatch an event with no data, like older versions of this crate.
+    ///
+    /// Each extra blank line between events produces an empty event.
+    Legacy,
+}
+
+/// The newline written after each line by the encoder
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+
+    /// `\r\n`
+    CrLf,
+
+    /// `\r`
+    Cr,
+}
+
+impl LineEnding {
+    /// Get the bytes of this line ending.
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Self::Lf => b"\n",
+            Self::CrLf => b"\r\n",
+            Self::Cr => b"\r",
         }
     }
+}
 
-    #[tokio::test]
-    async fn field_colon_space() {
-        let test_data = "data:test\n\ndata: test\n\n";
-        let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
-        let event_1 = reader
-            .next()
-            .await
-            .expect("missing event 1")
-            .expect("failed to parse");
-        let expected_event = SseEvent {
-            event: None,
-            data: Some("test".into()),
-            id: None,
-            retry: None,
-        };
-        assert!(event_1 == expected_event);
+/// A complete line found by the line scanner
+#[derive(Debug)]
+pub(crate) struct Line {
+    /// The index of the first byte of the line.
+    pub(crate) start: usize,
 
-        let event_2 = reader
-            .next()
-            .await
-            .expect("missing event 2")
-            .expect("failed to parse");
-        assert!(event_2 == expected_event);
+    /// The index of the newline that ends the line.
+    pub(crate) end: usize,
 
-        let no_event_3 = reader.next().await.is_none();
-        assert!(no_event_3);
+    /// The number of bytes to consume, including the newline.
+    pub(crate) advance: usize,
+
+    /// Whether the line ended with a \r that may be followed by a \n in the next buffer.
+    pending_cr: bool,
+}
+
+impl Line {
+    /// Get the newline that ended the line.
+    ///
+    /// Returns `None` if the line ended with a \r at the end of the buffer, since it may be the start of a \r\n.
+    pub(crate) fn line_ending(&self, bytes: &[u8]) -> Option<LineEnding> {
+        match (bytes[self.end], self.advance - self.end) {
+            (b'\n', _) => Some(LineEnding::Lf),
+            (_, 2) => Some(LineEnding::CrLf),
+            _ if self.pending_cr => None,
+            _ => Some(LineEnding::Cr),
+        }
     }
+}
 
-    #[tokio::test]
-    async fn field_colon_space_r() {
-        let test_data = "data:test\r\rdata: test\r\r";
-        let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
-        let event_1 = reader
-            .next()
-            .await
-            .expect("missing event 1")
-            .expect("failed to parse");
-        let expected_event = SseEvent {
-            event: None,
-            data: Some("test".into()),
-            id: None,
-            retry: None,
+/// A scanner that splits a buffer into lines, handling all 3 kinds of newlines.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct LineScanner {
+    // Check if the last newline was a \r.
+    last_newline_cr: bool,
+
+    /// The number of lines consumed so far.
+    line: u64,
+
+    /// The number of bytes at the start of the buffer already known to not contain a newline.
+    ///
+    /// This avoids rescanning a long partial line each time more bytes are read.
+    scanned: usize,
+}
+
+impl LineScanner {
+    /// Find the next complete line in the buffer.
+    ///
+    /// The line is not consumed until [`LineScanner::consume`] is called.
+    /// If there is no complete line, the next call must be passed the same buffer, with more bytes appended.
+    pub(crate) fn next_line(&mut self, bytes: &[u8]) -> Option<Line> {
+        // Need to handle: \n, \r\n, \r
+        // If the last newline was \r, trim the \n if one occurs.
+        // This byte belongs to the previous line.
+        let start = self.line_start(bytes);
+
+        let scan_start = start.max(self.scanned.min(bytes.len()));
+        let end = match bytes[scan_start..]
+            .iter()
+            .position(|b| *b == b'\r' || *b == b'\n')
+        {
+            Some(index) => scan_start + index,
+            None => {
+                self.scanned = bytes.len();
+                return None;
+            }
         };
-        assert!(event_1 == expected_event);
 
-        let event_2 = reader
-            .next()
-            .await
-            .expect("missing event 2")
-            .expect("failed to parse");
-        assert!(event_2 == expected_event);
+        // To handle a multi-byte newline,
+        // we need to discard the next byte if the current newline is a \r and the next byte is a \n.
+        // If the \r is the last byte in the buffer, we can't know yet.
+        // Instead, set a flag and skip the extra \n then if needed.
+        let mut advance = end + 1;
+        let mut pending_cr = false;
+        if bytes[end] == b'\r' {
+            match bytes.get(end + 1) {
+                Some(b'\n') => advance += 1,
+                Some(_) => {}
+                None => pending_cr = true,
+            }
+        }
 
-        let no_event_3 = reader.next().await.is_none();
-        assert!(no_event_3);
+        Some(Line {
+            start,
+            end,
+            advance,
+            pending_cr,
+        })
     }
 
-    #[tokio::test]
-    async fn field_colon_space_rn() {
-        let test_data = "data:test\r\n\r\ndata: test\r\n\r\n";
-        let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
-        let event_1 = reader
-            .next()
-            .await
-            .expect("missing event 1")
-            .expect("failed to parse");
-        let expected_event = SseEvent {
-            event: None,
-            data: Some("test".into()),
-            id: None,
-            retry: None,
-        };
-        assert!(event_1 == expected_event);
+    /// Get the index of the first byte of the next line.
+    fn line_start(&self, bytes: &[u8]) -> usize {
+        usize::from(self.last_newline_cr && bytes.first() == Some(&b'\n'))
+    }
 
-        let event_2 = reader
-            .next()
-            .await
-            .expect("missing event 2")
-            .expect("failed to parse");
-        assert!(event_2 == expected_event);
+    /// Get the length of the partial line at the start of a buffer with no complete lines.
+    pub(crate) fn partial_line_len(&self, bytes: &[u8]) -> usize {
+        bytes.len() - self.line_start(bytes)
+    }
 
-        let no_event_3 = reader.next().await.is_none();
-        assert!(no_event_3);
+    /// Validate a line as utf8.
+    pub(crate) fn line_str<'a>(&self, bytes: &'a [u8]) -> Result<&'a str, SseCodecError> {
+        std::str::from_utf8(bytes).map_err(|source| SseCodecError::InvalidUtf8 {
+            line: self.line + 1,
+            source,
+        })
     }
 
-    #[tokio::test]
-    async fn trailing_nl() {
-        let test_data = "data\n\ndata\ndata\n\ndata:";
-        let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
-        let event_1 = reader
-            .next()
-            .await
-            .expect("missing event 1")
-            .expect("failed to parse");
-        let expected_event_1 = SseEvent {
-            event: None,
-            data: Some("".into()),
-            id: None,
-            retry: None,
-        };
-        assert!(event_1 == expected_event_1);
+    /// Mark a line as consumed.
+    ///
+    /// The caller is responsible for advancing the buffer.
+    pub(crate) fn consume(&mut self, line: &Line) {
+        self.last_newline_cr = line.pending_cr;
+        self.line += 1;
+        self.scanned = 0;
+    }
+
+    /// Skip the \n at the start of a buffer with no complete lines, if it ends a \r\n newline.
+    ///
+    /// Returns the number of bytes skipped, which the caller must advance the buffer by.
+    pub(crate) fn skip_pending_lf(&mut self, bytes: &[u8]) -> usize {
+        let skipped = self.line_start(bytes);
+        if skipped > 0 {
+            self.last_newline_cr = false;
+            self.scanned = self.scanned.saturating_sub(skipped);
+        }
+        skipped
+    }
+
+    /// Forget about a partial line that was discarded.
+    pub(crate) fn discard_partial(&mut self) {
+        self.scanned = 0;
+    }
+}
+
+/// Parse the value of a `Last-Event-ID` request header, sent by a reconnecting client.
+///
+/// Surrounding whitespace is trimmed, since HTTP does not preserve it.
+/// Returns `None` if the value is empty, or if it could not have been sent as an id field,
+/// because it contains a NUL or a newline.
+pub fn parse_last_event_id(header_value: &str) -> Option<String> {
+    let id = header_value.trim_matches([' ', '\t']);
+    if id.is_empty() || id.contains(['\0', '\r', '\n']) {
+        return None;
+    }
+
+    Some(id.to_string())
+}
+
+/// Split a line into a field and a value.
+///
+/// The colon is removed, but the leading space of the value is not.
+/// Returns `None` if the line is a comment.
+pub(crate) fn split_field(line: &str) -> Option<(&str, &str)> {
+    match line.bytes().position(|b| b == b':') {
+        Some(0) => None,
+        Some(index) => {
+            let (field, value) = line.split_at(index);
+            // Trim the :
+            Some((field, &value[1..]))
+        }
+        None => Some((line, "")),
+    }
+}
+
+/// Trim a single leading space from a value, if present.
+pub(crate) fn trim_value(value: &str) -> &str {
+    value.strip_prefix(' ').unwrap_or(value)
+}
+
+/// The callback type of a [`LineInspector`].
+type LineInspectorFn = dyn FnMut(&str) + Send;
+
+/// A callback invoked for each complete line
+///
+/// This is shared between continuations of a codec.
+#[derive(Clone)]
+struct LineInspector(Arc<Mutex<LineInspectorFn>>);
+
+impl LineInspector {
+    /// Invoke the callback.
+    fn inspect(&self, line: &str) {
+        let mut inspector = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        (inspector)(line);
+    }
+}
+
+impl std::fmt::Debug for LineInspector {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("LineInspector").finish_non_exhaustive()
+    }
+}
+
+/// The callback type of a [`LineEndingInspector`].
+type LineEndingInspectorFn = dyn FnMut(LineEnding) + Send;
+
+/// A callback invoked with the newline of each complete line
+///
+/// This is shared between continuations of a codec.
+#[derive(Clone)]
+struct LineEndingInspector(Arc<Mutex<LineEndingInspectorFn>>);
+
+impl LineEndingInspector {
+    /// Invoke the callback.
+    fn inspect(&self, line_ending: LineEnding) {
+        let mut inspector = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        (inspector)(line_ending);
+    }
+}
+
+impl std::fmt::Debug for LineEndingInspector {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("LineEndingInspector")
+            .finish_non_exhaustive()
+    }
+}
+
+/// The callback type of a [`FirstEventHook`].
+type FirstEventHookFn = dyn FnOnce() + Send;
+
+/// A callback invoked when the first event is dispatched
+///
+/// This is shared between continuations of a codec, so it is invoked at most once.
+#[derive(Clone)]
+struct FirstEventHook(Arc<Mutex<Option<Box<FirstEventHookFn>>>>);
+
+impl FirstEventHook {
+    /// Invoke the callback, if it was not already invoked.
+    fn fire(&self) {
+        let hook = self.0.lock().unwrap_or_else(PoisonError::into_inner).take();
+        if let Some(hook) = hook {
+            hook();
+        }
+    }
+}
+
+impl std::fmt::Debug for FirstEventHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("FirstEventHook").finish_non_exhaustive()
+    }
+}
+
+/// An sse codec
+///
+/// This is a [`Decoder`] around an [`SseParser`], which holds all of the parsing state.
+#[derive(Debug)]
+pub struct SseCodec {
+    /// The parser
+    parser: SseParser,
+}
+
+impl SseCodec {
+    /// Make a new SSE Event decoder.
+    pub fn new() -> Self {
+        Self {
+            parser: SseParser::new(),
+        }
+    }
+
+    /// Make a builder for an SSE Event decoder.
+    pub fn builder() -> SseCodecBuilder {
+        SseCodecBuilder::new()
+    }
+
+    /// Make a new SSE Event decoder for a new connection, continuing from this one.
+    ///
+    /// The last event id and configuration are preserved,
+    /// while any partially decoded event is not.
+    pub fn new_with_continuation(&self) -> Self {
+        Self {
+            parser: self.parser.new_with_continuation(),
+        }
+    }
+
+    /// Get the approximate number of bytes of memory held by the decoder's buffers.
+    ///
+    /// This includes the fields of the partially decoded event, but not the input buffer.
+    pub fn memory_usage(&self) -> usize {
+        let strings = [
+            self.parser.event.as_ref(),
+            self.parser.data.as_ref(),
+            self.parser.id.as_ref(),
+            self.parser.last_event_id.as_ref(),
+            Some(&self.parser.spare.event),
+            Some(&self.parser.spare.data),
+            Some(&self.parser.spare.id),
+        ];
+
+        strings
+            .into_iter()
+            .flatten()
+            .chain(&self.parser.id_history)
+            .map(String::capacity)
+            .sum::<usize>()
+            + self.parser.raw.as_ref().map_or(0, Vec::capacity)
+    }
+
+    /// Get the last retry value received, in milliseconds.
+    ///
+    /// Unlike the retry field of events, this is updated even if the retry field is in a block with no data.
+    pub fn last_retry(&self) -> Option<u64> {
+        self.parser.last_retry
+    }
+
+    /// Get the time to wait before reconnecting.
+    ///
+    /// This is the last retry value received, or the default reconnect delay if there was none,
+    /// clamped to the minimum and maximum reconnect delays.
+    /// A retry value of 0 asks for an immediate reconnect, so it results in the minimum delay,
+    /// or 100 milliseconds if there is no minimum, to avoid reconnecting in a tight loop.
+    pub fn next_reconnect_delay(&self) -> Duration {
+        let config = &self.parser.config;
+        let delay = match self.parser.last_retry {
+            Some(0) => config
+                .min_reconnect_delay
+                .unwrap_or(self::config::ZERO_RETRY_DELAY),
+            Some(retry) => Duration::from_millis(retry),
+            None => config.default_reconnect_delay,
+        };
+        let delay = match config.min_reconnect_delay {
+            Some(min_reconnect_delay) => delay.max(min_reconnect_delay),
+            None => delay,
+        };
+
+        match self.parser.config.max_reconnect_delay {
+            Some(max_reconnect_delay) => delay.min(max_reconnect_delay),
+            None => delay,
+        }
+    }
+
+    /// Reset the per-event state of the decoder, keeping its configuration and stream-global state.
+    ///
+    /// A decoder keeps any partially decoded event between calls,
+    /// so it should be reset before it is reused for a new connection to the same stream.
+    /// The last event id, last retry value, and id history are kept, like [`SseCodec::new_with_continuation`].
+    /// Use [`SseCodec::reset_all`] to also clear them for an unrelated stream.
+    pub fn reset(&mut self) {
+        *self = self.new_with_continuation();
+    }
+
+    /// Reset the decoder to its initial state, keeping only its configuration.
+    ///
+    /// Unlike [`SseCodec::reset`], this also clears the last event id, last retry value, and id history.
+    pub fn reset_all(&mut self) {
+        let mut codec = self.new_with_continuation();
+        codec.parser.last_event_id = None;
+        codec.parser.last_retry = None;
+        codec.parser.id_history.clear();
+        *self = codec;
+    }
+
+    /// Get the id of the last dispatched event that had one.
+    ///
+    /// This is the value that should be sent in the `Last-Event-ID` header when reconnecting.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.parser.last_event_id.as_deref()
+    }
+
+    /// Clear the last event id, without changing the partially decoded event.
+    ///
+    /// This can be used to resync from the start of a stream on the next reconnect.
+    /// If the partially decoded event has an id, it still becomes the last event id once it is completed.
+    pub fn clear_last_event_id(&mut self) {
+        self.parser.last_event_id = None;
+    }
+
+    /// Set whether a single leading space is trimmed from the value of the given field.
+    ///
+    /// Only the leading space is trimmed; trailing spaces are always kept.
+    /// For example, `data:` and `data: ` both have an empty value,
+    /// while `data:  ` has a value of a single space.
+    /// See [`SseCodec::with_preserve_trailing_space`] to keep every space of values made only of spaces.
+    ///
+    /// By default, this is enabled for all fields, per spec.
+    pub fn with_field_trim(mut self, field: impl Into<String>, trim: bool) -> Self {
+        self.parser.config.field_trim.insert(field.into(), trim);
+        self
+    }
+
+    /// Set whether a partial event is dispatched at the end of the stream.
+    ///
+    /// Per spec, an event that is not terminated by a blank line is discarded at the end of the stream.
+    /// When this is enabled, the end of the stream is treated as a newline followed by a blank line,
+    /// so an unterminated last line is parsed and the buffered event is dispatched.
+    /// This is useful for servers that close the connection instead of sending a final blank line.
+    /// By default, this is disabled.
+    pub fn with_eof_flush(mut self, eof_flush: bool) -> Self {
+        self.parser.config.eof_flush = eof_flush;
+        self
+    }
+
+    /// Keep a history of the most recent ids, up to the given capacity.
+    ///
+    /// This is a debugging aid, for checking that a server resumed from the right id after a reconnect.
+    /// The history is kept by continuations of this codec.
+    /// By default, the capacity is 0, so no history is kept.
+    pub fn with_id_history(mut self, capacity: usize) -> Self {
+        self.parser.config.id_history_capacity = capacity;
+        self.parser.id_history.truncate(capacity);
+        self
+    }
+
+    /// Get the most recent ids, oldest first.
+    ///
+    /// An id is added each time a block with an id is completed, even if no event was dispatched.
+    /// See [`SseCodec::with_id_history`].
+    pub fn id_history(&self) -> &VecDeque<String> {
+        &self.parser.id_history
+    }
+
+    /// Enable counting the bytes consumed and events dispatched.
+    ///
+    /// The counters can be read with a handle from [`SseCodec::stats_handle`].
+    /// They are shared with continuations of this codec, so they include all connections.
+    pub fn with_stats(mut self) -> Self {
+        self.parser.stats.get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Get a handle for reading the stats of this codec, if enabled.
+    ///
+    /// See [`SseCodec::with_stats`].
+    pub fn stats_handle(&self) -> Option<SseStatsHandle> {
+        self.parser.stats.clone().map(SseStatsHandle::new)
+    }
+
+    /// Set whether discarding a partial event at the end of the stream is an error.
+    ///
+    /// When this is enabled, [`Decoder::decode_eof`] returns [`SseCodecError::UnterminatedEvent`]
+    /// if the stream ends with a partial line or fields that were not completed by a blank line.
+    /// This surfaces truncated streams to strict consumers.
+    /// [`SseCodec::with_eof_flush`] takes precedence, since it keeps the partial event.
+    /// By default, this is disabled, and partial events are silently discarded, per spec.
+    pub fn with_eof_error(mut self, eof_error: bool) -> Self {
+        self.parser.config.eof_error = eof_error;
+        self
+    }
+
+    /// Set the maximum number of lines consumed by a single call to [`Decoder::decode`].
+    ///
+    /// Once this many lines are consumed without completing an event, decode returns `Ok(None)`,
+    /// keeping its progress, so that a long run of comments or blank lines cannot monopolize a task.
+    /// Note that [`FramedRead`](tokio_util::codec::FramedRead) tries to read more bytes before calling decode again,
+    /// so the remaining lines are decoded once more bytes arrive or the stream ends.
+    /// [`Decoder::decode_eof`] is not limited.
+    /// By default, there is no limit.
+    pub fn with_max_lines_per_poll(mut self, max_lines_per_poll: usize) -> Self {
+        self.parser.config.max_lines_per_poll = Some(max_lines_per_poll);
+        self
+    }
+
+    /// Set the line that dispatches an event.
+    ///
+    /// Some non-standard streams separate events with a line like `;` instead of a blank line.
+    /// By default, this is [`EventSeparator::BlankLine`], per spec.
+    pub fn with_event_separator(mut self, event_separator: EventSeparator) -> Self {
+        self.parser.config.event_separator = event_separator;
+        self
+    }
+
+    /// Set how a separator is handled when the event has no data.
+    ///
+    /// By default, this is [`BlankLinePolicy::Spec`], which does not dispatch an event.
+    pub fn with_blank_line_policy(mut self, blank_line_policy: BlankLinePolicy) -> Self {
+        self.parser.config.blank_line_policy = blank_line_policy;
+        self
+    }
+
+    /// Set whether values made only of spaces are kept as-is.
+    ///
+    /// For these values, the leading space is also a trailing space,
+    /// so it is not trimmed when this is enabled.
+    /// For example, `data: ` has a value of a single space instead of an empty value.
+    /// Values with any other characters are trimmed as usual.
+    /// By default, this is disabled, per spec.
+    pub fn with_preserve_trailing_space(mut self, preserve_trailing_space: bool) -> Self {
+        self.parser.config.preserve_trailing_space = preserve_trailing_space;
+        self
+    }
+
+    /// Add alternate names for fields, mapping each alias to a field name.
+    ///
+    /// Aliased fields are handled exactly like the field they map to,
+    /// including the options for that field.
+    /// This is useful for dialects of SSE that use different field names.
+    pub fn with_field_aliases<I, K, V>(mut self, aliases: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.parser.config.field_aliases.extend(
+            aliases
+                .into_iter()
+                .map(|(alias, field)| (alias.into(), field.into())),
+        );
+        self
+    }
+
+    /// Set the maximum length of a line, in bytes, excluding the newline.
+    ///
+    /// Longer lines, including comments, produce an error as soon as they exceed the limit,
+    /// even if the newline has not been received yet.
+    /// The length is counted in raw bytes, before utf8 validation,
+    /// so a partial multibyte character at the end of the buffer is kept until the rest of it arrives.
+    /// By default, there is no limit.
+    pub fn with_max_line_length(mut self, max_line_length: usize) -> Self {
+        self.parser.config.max_line_length = Some(max_line_length);
+        self
+    }
+
+    /// Set the maximum number of bytes consumed from a stream.
+    ///
+    /// Once a stream exceeds the limit, decoding produces [`SseCodecError::StreamTooLarge`],
+    /// even if the newline of the last line has not been received yet.
+    /// This protects against endless streams when a bounded response is expected.
+    /// The count starts again for continuations, see [`SseCodec::new_with_continuation`].
+    /// By default, there is no limit.
+    pub fn with_max_stream_bytes(mut self, max_stream_bytes: usize) -> Self {
+        self.parser.config.max_stream_bytes = Some(max_stream_bytes);
+        self
+    }
+
+    /// Set the reconnect delay used until the server sends a retry field.
+    ///
+    /// See [`SseCodec::next_reconnect_delay`].
+    /// By default, this is 3 seconds.
+    pub fn with_default_reconnect_delay(mut self, default_reconnect_delay: Duration) -> Self {
+        self.parser.config.default_reconnect_delay = default_reconnect_delay;
+        self
+    }
+
+    /// Set the minimum reconnect delay, even if the server sends a smaller retry field.
+    ///
+    /// This also replaces the delay used if the server sends `retry: 0`.
+    /// If it is larger than the maximum reconnect delay, the maximum is used.
+    /// See [`SseCodec::next_reconnect_delay`].
+    /// By default, there is no minimum, but a retry of 0 still waits 100 milliseconds.
+    pub fn with_min_reconnect_delay(mut self, min_reconnect_delay: Duration) -> Self {
+        self.parser.config.min_reconnect_delay = Some(min_reconnect_delay);
+        self
+    }
+
+    /// Set the maximum reconnect delay, even if the server sends a larger retry field.
+    ///
+    /// See [`SseCodec::next_reconnect_delay`].
+    /// By default, there is no limit.
+    pub fn with_max_reconnect_delay(mut self, max_reconnect_delay: Duration) -> Self {
+        self.parser.config.max_reconnect_delay = Some(max_reconnect_delay);
+        self
+    }
+
+    /// Set whether lines that are not valid utf8 are skipped, instead of producing an error.
+    ///
+    /// Skipped lines are reported by [`SseCodec::decode_line`] as [`DecodeProgress::SkippedInvalidUtf8`].
+    /// By default, this is disabled.
+    pub fn with_utf8_recovery(mut self, utf8_recovery: bool) -> Self {
+        self.parser.config.utf8_recovery = utf8_recovery;
+        self
+    }
+
+    /// Set a callback that is invoked for each complete line, before it is split into a field and value.
+    ///
+    /// This is a debugging aid for inspecting what a server sends, and does not change the decoded events.
+    /// Newlines are not included, and lines that are not valid utf8 are not passed to the callback.
+    /// The callback is shared with continuations of this codec.
+    pub fn with_line_inspector<F>(mut self, line_inspector: F) -> Self
+    where
+        F: FnMut(&str) + Send + 'static,
+    {
+        self.parser.line_inspector = Some(LineInspector(Arc::new(Mutex::new(line_inspector))));
+        self
+    }
+
+    /// Set a callback that is invoked with the newline of each complete line.
+    ///
+    /// This is a debugging aid for detecting servers that mix newlines.
+    /// A `\r\n` is reported once, as [`LineEnding::CrLf`].
+    /// If a buffer ends with a `\r`, it is reported once the next byte is known, or at the end of the stream.
+    /// The callback is shared with continuations of this codec.
+    pub fn with_line_ending_inspector<F>(mut self, line_ending_inspector: F) -> Self
+    where
+        F: FnMut(LineEnding) + Send + 'static,
+    {
+        self.parser.line_ending_inspector = Some(LineEndingInspector(Arc::new(Mutex::new(
+            line_ending_inspector,
+        ))));
+        self
+    }
+
+    /// Set a callback that is invoked once, when the first event is dispatched.
+    ///
+    /// This is useful for measuring the time to the first event of a connection.
+    /// The callback is shared with continuations of this codec, so it is still only invoked once.
+    pub fn with_first_event_hook<F>(mut self, first_event_hook: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.parser.first_event_hook = Some(FirstEventHook(Arc::new(Mutex::new(Some(Box::new(
+            first_event_hook,
+        ))))));
+        self
+    }
+
+    /// Decode lines until an event is completed, more bytes are needed, or `max_lines` lines are consumed.
+    fn decode_event(
+        &mut self,
+        bytes: &mut BytesMut,
+        max_lines: Option<usize>,
+    ) -> Result<Option<SseEvent>, SseCodecError> {
+        let mut lines = 0;
+        loop {
+            if max_lines.is_some_and(|max_lines| lines >= max_lines) {
+                return Ok(None);
+            }
+
+            match self.decode_line(bytes)? {
+                DecodeProgress::Field
+                | DecodeProgress::Comment
+                | DecodeProgress::SkippedInvalidUtf8
+                | DecodeProgress::BlankLine => {}
+                DecodeProgress::Event(event) => return Ok(Some(event)),
+                DecodeProgress::Blocked => return Ok(None),
+            }
+            lines += 1;
+        }
+    }
+
+    /// Check if the start of a stream looks like SSE.
+    ///
+    /// This is a best-effort heuristic for content sniffing, when the content type is missing or wrong.
+    /// It skips a leading byte order mark and blank lines,
+    /// and then checks for a comment or a known field name followed by a colon or newline.
+    /// A valid SSE stream may not pass, and other content may pass.
+    pub fn looks_like_sse(prefix: &[u8]) -> bool {
+        let prefix = prefix.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(prefix);
+        let start = match prefix.iter().position(|b| *b != b'\r' && *b != b'\n') {
+            Some(start) => start,
+            None => return false,
+        };
+        let line = &prefix[start..];
+
+        if line.starts_with(b":") {
+            return true;
+        }
+
+        ["event", "data", "id", "retry"].iter().any(|field| {
+            line.strip_prefix(field.as_bytes())
+                .and_then(|rest| rest.first())
+                .is_some_and(|b| matches!(b, b':' | b'\r' | b'\n'))
+        })
+    }
+
+    /// Decode up to `limit` complete events from the buffer.
+    ///
+    /// The rest of the buffer is left for the next call, which bounds the size of the returned batch.
+    /// Decoding stops at the first error, which is returned along with the events decoded before it,
+    /// so neither is lost.
+    pub fn decode_batch(
+        &mut self,
+        bytes: &mut BytesMut,
+        limit: usize,
+    ) -> (Vec<SseEvent>, Option<SseCodecError>) {
+        let mut events = Vec::new();
+        while events.len() < limit {
+            match self.decode(bytes) {
+                Ok(Some(event)) => events.push(event),
+                Ok(None) => break,
+                Err(error) => return (events, Some(error)),
+            }
+        }
+
+        (events, None)
+    }
+
+    /// Decode at most one line from the buffer, reporting what kind of progress was made.
+    ///
+    /// This is a lower-level alternative to [`Decoder::decode`],
+    /// useful for diagnosing streams that do not produce events.
+    pub fn decode_line(&mut self, bytes: &mut BytesMut) -> Result<DecodeProgress, SseCodecError> {
+        let (progress, consumed) = self.parser.decode_line_slice(bytes)?;
+        bytes.advance(consumed);
+
+        Ok(progress)
+    }
+
+    /// Decode at most one event from a slice, without copying it into a buffer.
+    ///
+    /// Returns the event, if one was completed, and the number of bytes consumed.
+    /// Unconsumed bytes must be passed again at the start of the next call, followed by any new bytes.
+    /// The decoder keeps its state between calls,
+    /// so a `\r\n` newline split across two slices is only counted once.
+    pub fn decode_slice(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<(Option<SseEvent>, usize), SseCodecError> {
+        self.parser.decode_slice(bytes)
+    }
+
+    /// Check if the last consumed line ended with a `\r`.
+    ///
+    /// If so, a `\n` at the start of the next bytes is part of that newline, and is skipped.
+    pub fn last_newline_was_cr(&self) -> bool {
+        self.parser.lines.last_newline_cr
+    }
+}
+
+impl Decoder for SseCodec {
+    type Item = SseEvent;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.decode_event(bytes, self.parser.config.max_lines_per_poll)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // A trailing \r can no longer be followed by a \n.
+        if buf.is_empty() {
+            self.parser.resolve_pending_cr(false);
+        }
+
+        // The stream is over, so there is nothing to wait for, and all lines must be decoded.
+        match self.decode_event(buf, None)? {
+            Some(frame) => Ok(Some(frame)),
+            None if self.parser.needs_eof_flush(buf) => {
+                buf.extend_from_slice(&self.parser.eof_terminator());
+                self.decode_event(buf, None)
+            }
+            None => {
+                // Decode will only return None if it is passed an empty buffer or not have a trailing newline.
+                let result = self.parser.discard_at_eof(buf);
+                buf.clear();
+                result.map(|()| None)
+            }
+        }
+    }
+}
+
+impl Default for SseCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A builder for an [`SseCodec`]
+///
+/// # Example
+/// ```
+/// use nd_tokio_sse_codec::SseCodec;
+///
+/// let codec = SseCodec::builder()
+///     .max_line_length(64 * 1024)
+///     .field_trim("id", false)
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct SseCodecBuilder {
+    codec: SseCodec,
+}
+
+impl SseCodecBuilder {
+    /// Make a new builder with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            codec: SseCodec::new(),
+        }
+    }
+
+    /// Set the maximum length of a line, in bytes.
+    ///
+    /// See [`SseCodec::with_max_line_length`].
+    pub fn max_line_length(mut self, max_line_length: usize) -> Self {
+        self.codec = self.codec.with_max_line_length(max_line_length);
+        self
+    }
+
+    /// Set the maximum number of bytes consumed from a stream.
+    ///
+    /// See [`SseCodec::with_max_stream_bytes`].
+    pub fn max_stream_bytes(mut self, max_stream_bytes: usize) -> Self {
+        self.codec = self.codec.with_max_stream_bytes(max_stream_bytes);
+        self
+    }
+
+    /// Set the reconnect delay used until the server sends a retry field.
+    ///
+    /// See [`SseCodec::with_default_reconnect_delay`].
+    pub fn default_reconnect_delay(mut self, default_reconnect_delay: Duration) -> Self {
+        self.codec = self
+            .codec
+            .with_default_reconnect_delay(default_reconnect_delay);
+        self
+    }
+
+    /// Set the minimum reconnect delay.
+    ///
+    /// See [`SseCodec::with_min_reconnect_delay`].
+    pub fn min_reconnect_delay(mut self, min_reconnect_delay: Duration) -> Self {
+        self.codec = self.codec.with_min_reconnect_delay(min_reconnect_delay);
+        self
+    }
+
+    /// Set the maximum reconnect delay.
+    ///
+    /// See [`SseCodec::with_max_reconnect_delay`].
+    pub fn max_reconnect_delay(mut self, max_reconnect_delay: Duration) -> Self {
+        self.codec = self.codec.with_max_reconnect_delay(max_reconnect_delay);
+        self
+    }
+
+    /// Set whether lines that are not valid utf8 are skipped.
+    ///
+    /// See [`SseCodec::with_utf8_recovery`].
+    pub fn utf8_recovery(mut self, utf8_recovery: bool) -> Self {
+        self.codec = self.codec.with_utf8_recovery(utf8_recovery);
+        self
+    }
+
+    /// Set whether a single leading space is trimmed from the value of the given field.
+    ///
+    /// See [`SseCodec::with_field_trim`].
+    pub fn field_trim(mut self, field: impl Into<String>, trim: bool) -> Self {
+        self.codec = self.codec.with_field_trim(field, trim);
+        self
+    }
+
+    /// Set whether a partial event is dispatched at the end of the stream.
+    ///
+    /// See [`SseCodec::with_eof_flush`].
+    pub fn eof_flush(mut self, eof_flush: bool) -> Self {
+        self.codec = self.codec.with_eof_flush(eof_flush);
+        self
+    }
+
+    /// Set whether discarding a partial event at the end of the stream is an error.
+    ///
+    /// See [`SseCodec::with_eof_error`].
+    pub fn eof_error(mut self, eof_error: bool) -> Self {
+        self.codec = self.codec.with_eof_error(eof_error);
+        self
+    }
+
+    /// Set the maximum number of lines consumed by a single call to decode.
+    ///
+    /// See [`SseCodec::with_max_lines_per_poll`].
+    pub fn max_lines_per_poll(mut self, max_lines_per_poll: usize) -> Self {
+        self.codec = self.codec.with_max_lines_per_poll(max_lines_per_poll);
+        self
+    }
+
+    /// Set the line that dispatches an event.
+    ///
+    /// See [`SseCodec::with_event_separator`].
+    pub fn event_separator(mut self, event_separator: EventSeparator) -> Self {
+        self.codec = self.codec.with_event_separator(event_separator);
+        self
+    }
+
+    /// Set how a separator is handled when the event has no data.
+    ///
+    /// See [`SseCodec::with_blank_line_policy`].
+    pub fn blank_line_policy(mut self, blank_line_policy: BlankLinePolicy) -> Self {
+        self.codec = self.codec.with_blank_line_policy(blank_line_policy);
+        self
+    }
+
+    /// Set whether values made only of spaces are kept as-is.
+    ///
+    /// See [`SseCodec::with_preserve_trailing_space`].
+    pub fn preserve_trailing_space(mut self, preserve_trailing_space: bool) -> Self {
+        self.codec = self
+            .codec
+            .with_preserve_trailing_space(preserve_trailing_space);
+        self
+    }
+
+    /// Add alternate names for fields.
+    ///
+    /// See [`SseCodec::with_field_aliases`].
+    pub fn field_aliases<I, K, V>(mut self, aliases: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.codec = self.codec.with_field_aliases(aliases);
+        self
+    }
+
+    /// Set a callback that is invoked for each complete line.
+    ///
+    /// See [`SseCodec::with_line_inspector`].
+    pub fn line_inspector<F>(mut self, line_inspector: F) -> Self
+    where
+        F: FnMut(&str) + Send + 'static,
+    {
+        self.codec = self.codec.with_line_inspector(line_inspector);
+        self
+    }
+
+    /// Set a callback that is invoked with the newline of each complete line.
+    ///
+    /// See [`SseCodec::with_line_ending_inspector`].
+    pub fn line_ending_inspector<F>(mut self, line_ending_inspector: F) -> Self
+    where
+        F: FnMut(LineEnding) + Send + 'static,
+    {
+        self.codec = self.codec.with_line_ending_inspector(line_ending_inspector);
+        self
+    }
+
+    /// Set a callback that is invoked once, when the first event is dispatched.
+    ///
+    /// See [`SseCodec::with_first_event_hook`].
+    pub fn first_event_hook<F>(mut self, first_event_hook: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.codec = self.codec.with_first_event_hook(first_event_hook);
+        self
+    }
+
+    /// Build the codec.
+    pub fn build(self) -> SseCodec {
+        self.codec
+    }
+}
+
+/// Parse all events from a byte slice, without copying it into a buffer.
+///
+/// The end of the slice is treated as the end of the stream,
+/// so a trailing partial event is discarded.
+/// Iteration stops after the first error.
+pub fn parse_slice(mut input: &[u8]) -> impl Iterator<Item = Result<SseEvent, SseCodecError>> + '_ {
+    let mut codec = SseCodec::new();
+    let mut done = false;
+
+    std::iter::from_fn(move || {
+        while !done {
+            match codec.parser.decode_line_slice(input) {
+                Ok((progress, consumed)) => {
+                    input = &input[consumed..];
+                    match progress {
+                        DecodeProgress::Field
+                        | DecodeProgress::Comment
+                        | DecodeProgress::SkippedInvalidUtf8
+                        | DecodeProgress::BlankLine => {}
+                        DecodeProgress::Event(event) => return Some(Ok(event)),
+                        DecodeProgress::Blocked => done = true,
+                    }
+                }
+                Err(error) => {
+                    done = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+
+        None
+    })
+}
+
+/// An sse event, along with the raw bytes it was decoded from
+#[derive(Debug, PartialEq)]
+pub struct RawSseEvent {
+    /// The decoded event
+    pub event: SseEvent,
+
+    /// The raw bytes of the event, including the terminating blank line.
+    ///
+    /// If a `\r\n` newline was split across reads,
+    /// the trailing `\n` is not included.
+    pub raw: Bytes,
+}
+
+/// An sse codec that also captures the raw bytes of each event
+#[derive(Debug)]
+pub struct RawSseCodec {
+    codec: SseCodec,
+}
+
+impl RawSseCodec {
+    /// Make a new raw SSE Event decoder.
+    pub fn new() -> Self {
+        let mut codec = SseCodec::new();
+        codec.parser.raw = Some(Vec::new());
+
+        Self { codec }
+    }
+}
+
+impl Decoder for RawSseCodec {
+    type Item = RawSseEvent;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let event = match self.codec.decode(bytes)? {
+            Some(event) => event,
+            None => return Ok(None),
+        };
+        let raw = self
+            .codec
+            .parser
+            .raw
+            .as_mut()
+            .map(|raw| Bytes::from(std::mem::take(raw)))
+            .unwrap_or_default();
+
+        Ok(Some(RawSseEvent { event, raw }))
+    }
+
+    fn decode_eof(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(bytes)? {
+            Some(frame) => Ok(Some(frame)),
+            None => {
+                // Discard partial raw bytes, just like the inner codec.
+                self.codec.decode_eof(bytes)?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Default for RawSseCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    #[tokio::test]
+    async fn corpus() {
+        let mut dir_iter = tokio::fs::read_dir("corpus")
+            .await
+            .expect("failed to iter dir");
+
+        while let Some(entry) = dir_iter
+            .next_entry()
+            .await
+            .expect("failed to read next entry")
+        {
+            let test_data = tokio::fs::read_to_string(entry.path())
+                .await
+                .expect("failed to read test data");
+            let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+
+            while let Some(event) = reader.next().await {
+                let event = event.expect("failed to parse");
+                dbg!(event);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn field_colon_space() {
+        let test_data = "data:test\n\ndata: test\n\n";
+        let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let event_1 = reader
+            .next()
+            .await
+            .expect("missing event 1")
+            .expect("failed to parse");
+        let expected_event = SseEvent {
+            event: None,
+            data: Some("test".into()),
+            id: None,
+            retry: None,
+        };
+        assert!(event_1 == expected_event);
+
+        let event_2 = reader
+            .next()
+            .await
+            .expect("missing event 2")
+            .expect("failed to parse");
+        assert!(event_2 == expected_event);
+
+        let no_event_3 = reader.next().await.is_none();
+        assert!(no_event_3);
+    }
+
+    #[tokio::test]
+    async fn field_colon_space_r() {
+        let test_data = "data:test\r\rdata: test\r\r";
+        let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let event_1 = reader
+            .next()
+            .await
+            .expect("missing event 1")
+            .expect("failed to parse");
+        let expected_event = SseEvent {
+            event: None,
+            data: Some("test".into()),
+            id: None,
+            retry: None,
+        };
+        assert!(event_1 == expected_event);
+
+        let event_2 = reader
+            .next()
+            .await
+            .expect("missing event 2")
+            .expect("failed to parse");
+        assert!(event_2 == expected_event);
+
+        let no_event_3 = reader.next().await.is_none();
+        assert!(no_event_3);
+    }
+
+    #[tokio::test]
+    async fn field_colon_space_rn() {
+        let test_data = "data:test\r\n\r\ndata: test\r\n\r\n";
+        let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let event_1 = reader
+            .next()
+            .await
+            .expect("missing event 1")
+            .expect("failed to parse");
+        let expected_event = SseEvent {
+            event: None,
+            data: Some("test".into()),
+            id: None,
+            retry: None,
+        };
+        assert!(event_1 == expected_event);
 
         let event_2 = reader
             .next()
             .await
-            .expect("missing event 2")
+            .expect("missing event 2")
+            .expect("failed to parse");
+        assert!(event_2 == expected_event);
+
+        let no_event_3 = reader.next().await.is_none();
+        assert!(no_event_3);
+    }
+
+    #[tokio::test]
+    async fn trailing_nl() {
+        let test_data = "data\n\ndata\ndata\n\ndata:";
+        let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let event_1 = reader
+            .next()
+            .await
+            .expect("missing event 1")
+            .expect("failed to parse");
+        let expected_event_1 = SseEvent {
+            event: None,
+            data: Some("".into()),
+            id: None,
+            retry: None,
+        };
+        assert!(event_1 == expected_event_1);
+
+        let event_2 = reader
+            .next()
+            .await
+            .expect("missing event 2")
+            .expect("failed to parse");
+        let expected_event_2 = SseEvent {
+            event: None,
+            data: Some("\n".into()),
+            id: None,
+            retry: None,
+        };
+        assert!(event_2 == expected_event_2);
+
+        let no_event_3 = reader.next().await.is_none();
+        assert!(no_event_3);
+    }
+
+    #[tokio::test]
+    async fn raw_capture() {
+        let test_data =
+            "event: test\ndata: a\ndata: b\n\n: comment\r\ndata: c\r\r\n\r\ndata: d\n\n";
+        let mut reader = FramedRead::new(test_data.as_bytes(), RawSseCodec::new());
+
+        let mut raw_events = Vec::new();
+        while let Some(event) = reader.next().await {
+            raw_events.push(event.expect("failed to parse"));
+        }
+        assert!(raw_events.len() == 3);
+        assert!(&raw_events[0].raw[..] == b"event: test\ndata: a\ndata: b\n\n");
+        assert!(&raw_events[1].raw[..] == b": comment\r\ndata: c\r\r\n");
+        assert!(&raw_events[2].raw[..] == b"\r\ndata: d\n\n");
+
+        for raw_event in raw_events {
+            let mut reader = FramedRead::new(&raw_event.raw[..], SseCodec::new());
+            let event = reader
+                .next()
+                .await
+                .expect("missing event")
+                .expect("failed to parse");
+            assert!(event == raw_event.event);
+            assert!(reader.next().await.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn mixed_newlines() {
+        let test_data = "event: test\r\ndata: a\ndata: b\r\r\n";
+        let expected_event = SseEvent {
+            event: Some("test".into()),
+            data: Some("a\nb".into()),
+            id: None,
+            retry: None,
+        };
+
+        let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let event = reader
+            .next()
+            .await
+            .expect("missing event")
+            .expect("failed to parse");
+        assert!(event == expected_event);
+        assert!(reader.next().await.is_none());
+
+        // Feed the stream one byte at a time, so every \r lands at the end of the buffer.
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::new();
+        let mut events = Vec::new();
+        for byte in test_data.bytes() {
+            buffer.extend_from_slice(&[byte]);
+            while let Some(event) = codec.decode(&mut buffer).expect("failed to parse") {
+                events.push(event);
+            }
+        }
+        assert!(codec
+            .decode_eof(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(events == [expected_event]);
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn data_base64() {
+        let mut buffer = BytesMut::from("data: AAEC/w==\n\ndata: aGVs\ndata: bG8=\n\ndata: !\n\n");
+        let mut codec = SseCodec::new();
+        let mut next_event = || {
+            codec
+                .decode(&mut buffer)
+                .expect("failed to parse")
+                .expect("missing event")
+        };
+
+        let data = next_event().data_base64().expect("invalid base64");
+        assert!(data == [0, 1, 2, 255]);
+
+        let data = next_event().data_base64().expect("invalid base64");
+        assert!(data == b"hello");
+
+        assert!(next_event().data_base64().is_err());
+
+        let data = SseEvent::new().data_base64().expect("invalid base64");
+        assert!(data.is_empty());
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn data_json() {
+        let test_data =
+            "data: {\"name\": \"test\",\ndata: \"values\": [1, 2, 3]}\n\nevent: empty\ndata:\n\n";
+        let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+
+        let event_1 = reader
+            .next()
+            .await
+            .expect("missing event 1")
+            .expect("failed to parse");
+        let value: std::collections::HashMap<String, serde_json::Value> =
+            event_1.data_json().expect("failed to parse json");
+        assert!(value["name"] == "test");
+        assert!(value["values"] == serde_json::json!([1, 2, 3]));
+
+        let event_2 = reader
+            .next()
+            .await
+            .expect("missing event 2")
+            .expect("failed to parse");
+        assert!(event_2.data_json::<serde_json::Value>().is_err());
+    }
+
+    #[tokio::test]
+    async fn field_trim() {
+        let test_data = "id: x\ndata: y\n\n";
+        let codec = SseCodec::new().with_field_trim("id", false);
+        let mut reader = FramedRead::new(test_data.as_bytes(), codec);
+        let event = reader
+            .next()
+            .await
+            .expect("missing event")
+            .expect("failed to parse");
+        let expected_event = SseEvent {
+            event: None,
+            data: Some("y".into()),
+            id: Some(" x".into()),
+            retry: None,
+        };
+        assert!(event == expected_event);
+    }
+
+    #[test]
+    fn whitespace_values() {
+        // (line, value, value with trailing spaces preserved)
+        let cases = [
+            ("data", "", ""),
+            ("data:", "", ""),
+            ("data: ", "", " "),
+            ("data:  ", " ", "  "),
+            ("data:   ", "  ", "   "),
+            ("data:a ", "a ", "a "),
+            ("data: a ", "a ", "a "),
+            ("data: \t", "\t", "\t"),
+            ("data:\t ", "\t ", "\t "),
+        ];
+
+        for (line, value, preserved_value) in cases {
+            for (preserve, expected) in [(false, value), (true, preserved_value)] {
+                let mut codec = SseCodec::new().with_preserve_trailing_space(preserve);
+                let mut buffer = BytesMut::from(format!("{line}\n\n").as_str());
+                let event = codec
+                    .decode(&mut buffer)
+                    .expect("failed to parse")
+                    .expect("missing event");
+                assert!(
+                    event.data.as_deref() == Some(expected),
+                    "{line:?} with preserve={preserve} was {:?}",
+                    event.data
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_line() {
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from(&b"data: a\r\n: comment\rdata: \xFF\n\n"[..]);
+        let error = codec.decode(&mut buffer).expect_err("missing error");
+        assert!(matches!(error, SseCodecError::InvalidUtf8 { line: 3, .. }));
+    }
+
+    #[test]
+    fn new_with_continuation() {
+        let mut codec = SseCodec::new().with_field_trim("id", false);
+        let mut buffer = BytesMut::from("id: 1\ndata: a\n\ndata: b\n\nid: 2\ndata: c\n");
+        while codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_some()
+        {}
+        assert!(codec.last_event_id() == Some(" 1"));
+
+        let mut codec = codec.new_with_continuation();
+        assert!(codec.last_event_id() == Some(" 1"));
+
+        // The partial event should not carry over.
+        let mut buffer = BytesMut::from("data: d\n\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("d"));
+        assert!(event.id.is_none());
+
+        // The configuration should carry over.
+        let mut buffer = BytesMut::from("id: 3\n\n");
+        codec.decode(&mut buffer).expect("failed to parse");
+        assert!(codec.last_event_id() == Some(" 3"));
+    }
+
+    #[test]
+    fn decode_line_progress() {
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from(": a\n: b\ndata: c\n\ndata");
+
+        let mut progress = Vec::new();
+        loop {
+            let step = codec.decode_line(&mut buffer).expect("failed to parse");
+            let blocked = step == DecodeProgress::Blocked;
+            progress.push(step);
+            if blocked {
+                break;
+            }
+        }
+
+        let expected_progress = [
+            DecodeProgress::Comment,
+            DecodeProgress::Comment,
+            DecodeProgress::Field,
+            DecodeProgress::Event(SseEvent {
+                event: None,
+                data: Some("c".into()),
+                id: None,
+                retry: None,
+            }),
+            DecodeProgress::Blocked,
+        ];
+        assert!(progress == expected_progress);
+    }
+
+    #[test]
+    fn last_event_id_header() {
+        assert!(parse_last_event_id("42").as_deref() == Some("42"));
+        assert!(parse_last_event_id(" abc-1 \t").as_deref() == Some("abc-1"));
+        assert!(parse_last_event_id("a\0b").is_none());
+        assert!(parse_last_event_id("a\nb").is_none());
+        assert!(parse_last_event_id("").is_none());
+    }
+
+    #[test]
+    fn leading_newline() {
+        // With no buffered fields, a leading blank line is consumed without an event.
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from("\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(buffer.is_empty());
+
+        // With buffered fields from a previous call, it completes the event.
+        let mut buffer = BytesMut::from("data: a\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        buffer.extend_from_slice(b"\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event == SseEvent::new().with_data("a"));
+        assert!(buffer.is_empty());
+
+        // After a \r at the end of the previous buffer, a leading \n is part of that newline, not a blank line.
+        let mut buffer = BytesMut::from("data: b\r");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        buffer.extend_from_slice(b"\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        buffer.extend_from_slice(b"\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event == SseEvent::new().with_data("b"));
+    }
+
+    #[test]
+    fn mixed_terminator() {
+        for test_data in [
+            "data: x\n\r\n",
+            "data: x\r\n\n",
+            "data: x\r\n\r\n",
+            "data: x\r\r\n",
+        ] {
+            // Split the input at every position, so each newline may be split across buffers.
+            for split in 0..=test_data.len() {
+                let mut codec = SseCodec::new();
+                let mut buffer = BytesMut::from(&test_data[..split]);
+                let mut events = Vec::new();
+                while let Some(event) = codec.decode(&mut buffer).expect("failed to parse") {
+                    events.push(event);
+                }
+                buffer.extend_from_slice(&test_data.as_bytes()[split..]);
+                while let Some(event) = codec.decode(&mut buffer).expect("failed to parse") {
+                    events.push(event);
+                }
+
+                assert!(
+                    events == [SseEvent::new().with_data("x")],
+                    "{test_data:?} split at {split}"
+                );
+                assert!(buffer.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn data_colon_space_table() {
+        let table = [
+            ("data:", ""),
+            ("data: ", ""),
+            ("data:x", "x"),
+            ("data: x", "x"),
+            ("data:  x", " x"),
+            ("data", ""),
+        ];
+        for (line, expected) in table {
+            let mut codec = SseCodec::new();
+            let mut buffer = BytesMut::from(line);
+            buffer.extend_from_slice(b"\n");
+
+            // Each data line appends its value and a \n.
+            assert!(codec
+                .decode(&mut buffer)
+                .expect("failed to parse")
+                .is_none());
+            assert!(
+                codec.parser.data.as_deref() == Some(format!("{expected}\n").as_str()),
+                "{line:?}"
+            );
+
+            // The final \n is trimmed when the event is dispatched.
+            buffer.extend_from_slice(b"\n");
+            let event = codec
+                .decode(&mut buffer)
+                .expect("failed to parse")
+                .expect("missing event");
+            assert!(event.data.as_deref() == Some(expected), "{line:?}");
+        }
+    }
+
+    #[test]
+    fn empty_line_vs_empty_comment() {
+        // Only a line with no bytes at all dispatches; a colon alone or with a space is a comment.
+        let test_data = [
+            ("", DecodeProgress::Event(SseEvent::new().with_data("a"))),
+            (":", DecodeProgress::Comment),
+            (": ", DecodeProgress::Comment),
+        ];
+        for (line, expected) in test_data {
+            let mut codec = SseCodec::new();
+            let mut buffer = BytesMut::from(format!("data: a\n{line}\n").as_str());
+            assert!(
+                codec.decode_line(&mut buffer).expect("failed to parse") == DecodeProgress::Field
+            );
+            assert!(codec.decode_line(&mut buffer).expect("failed to parse") == expected);
+            assert!(buffer.is_empty());
+        }
+
+        // The comment text is everything after the colon, with the leading space trimmed like a value.
+        assert!(split_field(":").is_none());
+        assert!(split_field(": ").is_none());
+        assert!(trim_value(&": "[1..]).is_empty());
+    }
+
+    #[test]
+    fn comment_only_block() {
+        let mut buffer = BytesMut::from(":ping\n\n");
+        assert!(SseCodec::new()
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(buffer.is_empty());
+
+        // The comment is still surfaced, without an event.
+        let comments = Arc::new(Mutex::new(Vec::new()));
+        let mut codec = SseCodec::new().with_line_inspector({
+            let comments = comments.clone();
+            move |line| {
+                if let Some(comment) = line.strip_prefix(':') {
+                    comments.lock().unwrap().push(comment.to_string());
+                }
+            }
+        });
+        let mut buffer = BytesMut::from(":ping\n\n");
+        let progress = [
+            codec.decode_line(&mut buffer).expect("failed to parse"),
+            codec.decode_line(&mut buffer).expect("failed to parse"),
+            codec.decode_line(&mut buffer).expect("failed to parse"),
+        ];
+        assert!(
+            progress
+                == [
+                    DecodeProgress::Comment,
+                    DecodeProgress::BlankLine,
+                    DecodeProgress::Blocked
+                ]
+        );
+        assert!(*comments.lock().unwrap() == ["ping"]);
+    }
+
+    #[tokio::test]
+    async fn parse_slice_matches_decoder() {
+        let test_data = "event: a\ndata: 1\r\n\r\n: comment\rid: 2\rdata: 2\r\rdata: 3\n\ndata: 4";
+
+        let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let mut expected_events = Vec::new();
+        while let Some(event) = reader.next().await {
+            expected_events.push(event.expect("failed to parse"));
+        }
+
+        let events: Vec<_> = parse_slice(test_data.as_bytes())
+            .collect::<Result<_, _>>()
+            .expect("failed to parse");
+        assert!(events.len() == 3);
+        assert!(events == expected_events);
+
+        let mut iter = parse_slice(b"data: \xFF\n\ndata: a\n\n");
+        assert!(matches!(
+            iter.next(),
+            Some(Err(SseCodecError::InvalidUtf8 { line: 1, .. }))
+        ));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn comments_consumed_incrementally() {
+        let mut codec = SseCodec::new();
+
+        // An unterminated comment waits for more data.
+        let mut buffer = BytesMut::from(":comment");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(&buffer[..] == b":comment");
+
+        // A terminated comment is consumed, even without a following field.
+        buffer.extend_from_slice(b"\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(buffer.is_empty());
+
+        // Comments do not block dispatch.
+        buffer.extend_from_slice(b":a\ndata: x\n:b\n\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("x"));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn comment_max_line_length() {
+        let mut codec = SseCodec::new().with_max_line_length(8);
+
+        let mut buffer = BytesMut::from(":1234567\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(buffer.is_empty());
+
+        // The limit applies before the newline arrives.
+        let mut buffer = BytesMut::from(":12345678");
+        let error = codec.decode(&mut buffer).expect_err("missing error");
+        assert!(matches!(
+            error,
+            SseCodecError::LineTooLong { line: 2, limit: 8 }
+        ));
+    }
+
+    #[test]
+    fn max_stream_bytes() {
+        let mut codec = SseCodec::new().with_max_stream_bytes(20);
+        let mut buffer = BytesMut::from("data: a\n\ndata: b\n\ndata: c\n\n");
+
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("a"));
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("b"));
+
+        let error = codec.decode(&mut buffer).expect_err("missing error");
+        assert!(matches!(error, SseCodecError::StreamTooLarge { limit: 20 }));
+
+        // A continuation starts counting again.
+        let mut codec = codec.new_with_continuation();
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("c"));
+
+        // A partial line counts too.
+        let mut codec = SseCodec::new().with_max_stream_bytes(20);
+        let mut buffer = BytesMut::from(": this comment never ends");
+        let error = codec.decode(&mut buffer).expect_err("missing error");
+        assert!(matches!(error, SseCodecError::StreamTooLarge { limit: 20 }));
+    }
+
+    #[test]
+    fn max_line_length_multibyte() {
+        let mut codec = SseCodec::new().with_max_line_length(9);
+
+        // The buffer is under the limit, but ends in the middle of a 2 byte character.
+        let mut buffer = BytesMut::from(&b"data: a\xC3"[..]);
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(buffer.len() == 8);
+
+        buffer.extend_from_slice(b"\xA9\n\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("a\u{e9}"));
+
+        // Going over the limit mid-character is a length error, not a utf8 error.
+        let mut buffer = BytesMut::from(&b"data: abc\xC3"[..]);
+        let error = codec.decode(&mut buffer).expect_err("missing error");
+        assert!(matches!(
+            error,
+            SseCodecError::LineTooLong { line: 3, limit: 9 }
+        ));
+    }
+
+    #[tokio::test]
+    async fn utf8_recovery() {
+        let test_data = &b"data: a\n\ndata: \xFF\n\ndata: b\n\n"[..];
+        let codec = SseCodec::new().with_utf8_recovery(true);
+        let mut reader = FramedRead::new(test_data, codec);
+
+        let mut events = Vec::new();
+        while let Some(event) = reader.next().await {
+            events.push(event.expect("failed to parse"));
+        }
+
+        let data: Vec<_> = events.iter().map(|event| event.data.as_deref()).collect();
+        assert!(data == [Some("a"), Some("b")]);
+
+        let mut codec = SseCodec::new().with_utf8_recovery(true);
+        let mut buffer = BytesMut::from(&b"\xFF\n"[..]);
+        let progress = codec.decode_line(&mut buffer).expect("failed to parse");
+        assert!(progress == DecodeProgress::SkippedInvalidUtf8);
+    }
+
+    #[test]
+    fn reuse_without_reset() {
+        let mut codec = SseCodec::new();
+
+        let mut buffer = BytesMut::from("id: 1\ndata: a\n\ndata: partial\n");
+        while codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_some()
+        {}
+
+        // Without a reset, the partial event carries over into the next stream.
+        let mut buffer = BytesMut::from("data: b\n\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("partial\nb"));
+        assert!(codec.last_event_id() == Some("1"));
+    }
+
+    #[test]
+    fn reuse_with_reset() {
+        let mut codec = SseCodec::new().with_max_line_length(16);
+
+        let mut buffer = BytesMut::from("id: 1\ndata: a\n\ndata: partial\n\r");
+        while codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_some()
+        {}
+        codec.reset();
+        assert!(codec.last_event_id() == Some("1"));
+        codec.reset_all();
+        assert!(codec.last_event_id().is_none());
+
+        // A reset codec behaves like a new one, but keeps its configuration.
+        // The \n after the \r is a blank line, not part of a \r\n newline.
+        let mut buffer = BytesMut::from("\ndata: b\n\n");
+        let progress = codec.decode_line(&mut buffer).expect("failed to parse");
+        assert!(progress == DecodeProgress::BlankLine);
+
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("b"));
+
+        let mut buffer = BytesMut::from("data: 12345678901\n");
+        assert!(codec.decode(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn reset_keeps_last_event_id() {
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from("id: 1\nretry: 10\ndata: a\n\nid: 2\ndata: partial\n");
+        while codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_some()
+        {}
+
+        // The partial event is discarded, so its id never becomes the last event id.
+        codec.reset();
+        assert!(codec.last_event_id() == Some("1"));
+        assert!(codec.last_retry() == Some(10));
+        #[cfg(feature = "decoder")]
+        assert!(codec.in_progress().data_len.is_none());
+
+        codec.reset_all();
+        assert!(codec.last_event_id().is_none());
+        assert!(codec.last_retry().is_none());
+    }
+
+    #[test]
+    fn from_fields() {
+        let fields = [
+            ("event", "a"),
+            ("data", "1"),
+            ("id", "1"),
+            ("data", ""),
+            ("event", "b"),
+            ("id", "2\0"),
+            ("retry", "x"),
+            ("unknown", "c"),
+            ("data", " 3"),
+        ];
+        let event =
+            SseEvent::from_fields(fields.map(|(field, value)| (field.into(), value.into())));
+        assert!(
+            event
+                == SseEvent::new()
+                    .with_event("b")
+                    .with_data("1\n\n 3")
+                    .with_id("1")
+        );
+
+        // The same fields decode to the same event.
+        let test_data: String = fields
+            .iter()
+            .map(|(field, value)| format!("{field}: {value}\n"))
+            .collect();
+        let decoded = SseEvent::try_from(test_data.as_str()).expect("failed to parse");
+        assert!(decoded == event);
+    }
+
+    #[test]
+    fn colon_in_value() {
+        let test_data = "data: key:value\n\ndata:a:b:c\n\nid: urn:uuid:6e8bc430-9c3a-11d9-9669-0800200c9a66\ndata: http://example.com\n\n";
+        let events: Vec<_> = parse_slice(test_data.as_bytes())
+            .collect::<Result<_, _>>()
+            .expect("failed to parse");
+
+        let expected_events = [
+            SseEvent::new().with_data("key:value"),
+            SseEvent::new().with_data("a:b:c"),
+            SseEvent::new()
+                .with_data("http://example.com")
+                .with_id("urn:uuid:6e8bc430-9c3a-11d9-9669-0800200c9a66"),
+        ];
+        assert!(events == expected_events);
+    }
+
+    #[test]
+    fn double_colon() {
+        let test_data = "data::x\nevent::y\nid::z\n\ndata: :x\ndata:::\n\n";
+        let events: Vec<_> = parse_slice(test_data.as_bytes())
+            .collect::<Result<_, _>>()
+            .expect("failed to parse");
+
+        let expected_events = [
+            SseEvent::new()
+                .with_event(":y")
+                .with_data(":x")
+                .with_id(":z"),
+            SseEvent::new().with_data(":x\n::"),
+        ];
+        assert!(events == expected_events);
+    }
+
+    #[test]
+    fn space_before_colon() {
+        // Only a colon at the very start of a line makes a comment.
+        for line in [" : value", "  :value", "\t: value", " data: a", " :"] {
+            let mut codec = SseCodec::new();
+            let mut buffer = BytesMut::from(format!("{line}\n").as_str());
+            let progress = codec.decode_line(&mut buffer).expect("failed to parse");
+            assert!(progress == DecodeProgress::Field, "{line:?}");
+        }
+
+        let mut buffer = BytesMut::from(": comment\n");
+        let progress = SseCodec::new()
+            .decode_line(&mut buffer)
+            .expect("failed to parse");
+        assert!(progress == DecodeProgress::Comment);
+
+        // The fields are unknown, so they are ignored.
+        let test_data = " : value\n data: a\n :\ndata: b\n\n";
+        let events: Vec<_> = parse_slice(test_data.as_bytes())
+            .collect::<Result<_, _>>()
+            .expect("failed to parse");
+        assert!(events == [SseEvent::new().with_data("b")]);
+    }
+
+    #[tokio::test]
+    async fn field_aliases() {
+        let test_data = "e: update\nd: a\ndata: b\ni: 1\nx: ignored\n\n";
+        let codec =
+            SseCodec::new().with_field_aliases([("e", "event"), ("d", "data"), ("i", "id")]);
+        let mut reader = FramedRead::new(test_data.as_bytes(), codec);
+        let event = reader
+            .next()
+            .await
+            .expect("missing event")
             .expect("failed to parse");
-        let expected_event_2 = SseEvent {
-            event: None,
-            data: Some("\n".into()),
-            id: None,
-            retry: None,
+        let expected_event = SseEvent::new()
+            .with_event("update")
+            .with_data("a\nb")
+            .with_id("1");
+        assert!(event == expected_event);
+    }
+
+    #[test]
+    fn memory_usage() {
+        let mut codec = SseCodec::new();
+        assert!(codec.memory_usage() == 0);
+
+        let mut buffer = BytesMut::from("event: test\ndata: 0123456789\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        let usage_1 = codec.memory_usage();
+        assert!(usage_1 >= "test".len() + "0123456789\n".len());
+
+        buffer.extend_from_slice(b"data: 0123456789\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        let usage_2 = codec.memory_usage();
+        assert!(usage_2 > usage_1);
+
+        buffer.extend_from_slice(b"\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_some());
+        assert!(codec.memory_usage() == 0);
+    }
+
+    #[test]
+    fn blank_lines() {
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::new();
+        for _ in 0..10_000 {
+            buffer.extend_from_slice(b"\n");
+            buffer.extend_from_slice(b"\r\n");
+        }
+
+        // Blocks with no data are not dispatched,
+        // so every blank line is consumed in a single call.
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(buffer.is_empty());
+        assert!(codec.memory_usage() == 0);
+
+        let mut buffer = BytesMut::from("\r\n");
+        let progress = codec.decode_line(&mut buffer).expect("failed to parse");
+        assert!(progress == DecodeProgress::BlankLine);
+    }
+
+    #[test]
+    fn field_order() {
+        let expected_event = SseEvent::new()
+            .with_event("update")
+            .with_data("a\nb")
+            .with_id("1")
+            .with_retry(10);
+        let test_data = [
+            "id: 1\nevent: update\nretry: 10\ndata: a\ndata: b\n\n",
+            "data: a\ndata: b\nevent: update\nid: 1\nretry: 10\n\n",
+            "data: a\nid: 1\nretry: 10\nevent: update\ndata: b\n\n",
+            "retry: 10\ndata: a\nevent: update\n: comment\ndata: b\nid: 1\n\n",
+        ];
+
+        for test_data in test_data {
+            let mut codec = SseCodec::new();
+            let mut buffer = BytesMut::from(test_data);
+            let event = codec
+                .decode(&mut buffer)
+                .expect("failed to parse")
+                .expect("missing event");
+            assert!(event == expected_event, "{test_data:?}");
+            assert!(codec.last_event_id() == Some("1"));
+        }
+
+        // Later fields replace earlier ones, except data, which is joined.
+        let mut buffer = BytesMut::from("event: a\ndata: 1\nevent: b\nid: x\ndata: 2\nid: y\n\n");
+        let event = SseCodec::new()
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(
+            event
+                == SseEvent::new()
+                    .with_event("b")
+                    .with_data("1\n2")
+                    .with_id("y")
+        );
+    }
+
+    #[test]
+    fn blank_line_policy() {
+        let decode_all = |codec: &mut SseCodec| {
+            let mut buffer = BytesMut::from("data: a\n\n\n\ndata: b\n\n");
+            let mut events = Vec::new();
+            while let Some(event) = codec.decode(&mut buffer).expect("failed to parse") {
+                events.push(event);
+            }
+            events
         };
-        assert!(event_2 == expected_event_2);
 
-        let no_event_3 = reader.next().await.is_none();
-        assert!(no_event_3);
+        let events = decode_all(&mut SseCodec::new());
+        assert!(
+            events
+                == [
+                    SseEvent::new().with_data("a"),
+                    SseEvent::new().with_data("b")
+                ]
+        );
+
+        let events =
+            decode_all(&mut SseCodec::new().with_blank_line_policy(BlankLinePolicy::Legacy));
+        assert!(
+            events
+                == [
+                    SseEvent::new().with_data("a"),
+                    SseEvent::new(),
+                    SseEvent::new(),
+                    SseEvent::new().with_data("b")
+                ]
+        );
+    }
+
+    /// A reader that returns at most `chunk_size` bytes per read.
+    struct ChunkedReader {
+        bytes: Vec<u8>,
+        position: usize,
+        chunk_size: usize,
+    }
+
+    impl tokio::io::AsyncRead for ChunkedReader {
+        fn poll_read(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            let len = self
+                .chunk_size
+                .min(buf.remaining())
+                .min(self.bytes.len() - self.position);
+            buf.put_slice(&self.bytes[self.position..self.position + len]);
+            self.position += len;
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn long_data_line() {
+        const DATA_LEN: usize = 1024 * 1024;
+
+        let mut bytes = b"data: ".to_vec();
+        bytes.extend((0..DATA_LEN).map(|i| b'a' + (i % 26) as u8));
+        bytes.extend_from_slice(b"\r\n\r\n");
+
+        let reader = ChunkedReader {
+            bytes,
+            position: 0,
+            chunk_size: 1000,
+        };
+        let events: Vec<_> = FramedRead::new(reader, SseCodec::new())
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+
+        assert!(events.len() == 1);
+        let data = events[0].data.as_deref().expect("missing data");
+        assert!(data.len() == DATA_LEN);
+        assert!(data
+            .bytes()
+            .enumerate()
+            .all(|(i, b)| b == b'a' + (i % 26) as u8));
+    }
+
+    #[test]
+    fn huge_line_without_newline() {
+        const LINE_LEN: usize = 10 * 1024 * 1024;
+        const CHUNK_SIZE: usize = 4 * 1024;
+        const LIMIT: usize = 1024 * 1024;
+
+        let mut bytes = b"data: ".to_vec();
+        bytes.resize(LINE_LEN, b'a');
+
+        // Without a limit, the line is buffered, and each chunk is only scanned once.
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::new();
+        for chunk in bytes.chunks(CHUNK_SIZE) {
+            buffer.extend_from_slice(chunk);
+            assert!(codec
+                .decode(&mut buffer)
+                .expect("failed to parse")
+                .is_none());
+            assert!(codec.parser.lines.scanned == buffer.len());
+        }
+        buffer.extend_from_slice(b"\n\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.map(|data| data.len()) == Some(LINE_LEN - 6));
+
+        // With a limit, decoding errors on the first chunk that goes past it.
+        let mut codec = SseCodec::new().with_max_line_length(LIMIT);
+        let mut buffer = BytesMut::new();
+        let mut chunks = bytes.chunks(CHUNK_SIZE);
+        let error = loop {
+            buffer.extend_from_slice(chunks.next().expect("missing error"));
+            match codec.decode(&mut buffer) {
+                Ok(event) => assert!(event.is_none()),
+                Err(error) => break error,
+            }
+        };
+        assert!(buffer.len() == LIMIT + CHUNK_SIZE);
+        assert!(matches!(
+            error,
+            SseCodecError::LineTooLong {
+                line: 1,
+                limit: LIMIT
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn line_inspector() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let codec = SseCodec::new().with_line_inspector({
+            let lines = lines.clone();
+            move |line| lines.lock().unwrap().push(line.to_string())
+        });
+
+        let test_data = ": hi\r\nevent: test\ndata:a\rdata: b\n\nid: 1\n\n";
+        let events: Vec<_> = FramedRead::new(test_data.as_bytes(), codec)
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+        assert!(events == [SseEvent::new().with_event("test").with_data("a\nb")]);
+
+        let lines = lines.lock().unwrap();
+        assert!(*lines == [": hi", "event: test", "data:a", "data: b", "", "id: 1", ""]);
+    }
+
+    #[tokio::test]
+    async fn empty_stream() {
+        // A lone BOM is a partial line, so it is discarded at eof, whether or not it is stripped.
+        for test_data in ["", "\u{FEFF}"] {
+            let mut reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+            assert!(reader.next().await.is_none());
+            assert!(reader.decoder().last_event_id().is_none());
+        }
+    }
+
+    #[test]
+    fn line_ending_inspector() {
+        let test_data = "data: a\r\ndata: b\rdata: c\n\r\n: d\r";
+        let expected = [
+            LineEnding::CrLf,
+            LineEnding::Cr,
+            LineEnding::Lf,
+            LineEnding::CrLf,
+            LineEnding::Cr,
+        ];
+
+        // Feed the stream a byte at a time, so each \r\n is split.
+        let line_endings = Arc::new(Mutex::new(Vec::new()));
+        let mut codec = SseCodec::new().with_line_ending_inspector({
+            let line_endings = line_endings.clone();
+            move |line_ending| line_endings.lock().unwrap().push(line_ending)
+        });
+        let mut buffer = BytesMut::new();
+        let mut events = Vec::new();
+        for b in test_data.bytes() {
+            buffer.extend_from_slice(&[b]);
+            while let Some(event) = codec.decode(&mut buffer).expect("failed to parse") {
+                events.push(event);
+            }
+        }
+        while let Some(event) = codec.decode_eof(&mut buffer).expect("failed to parse") {
+            events.push(event);
+        }
+
+        assert!(events == [SseEvent::new().with_data("a\nb\nc")]);
+        assert!(*line_endings.lock().unwrap() == expected);
+    }
+
+    #[tokio::test]
+    async fn first_event_hook() {
+        let calls = Arc::new(Mutex::new(0));
+        let codec = SseCodec::new().with_first_event_hook({
+            let calls = calls.clone();
+            move || *calls.lock().unwrap() += 1
+        });
+
+        let test_data = ": hi\nretry: 10\n\ndata: 1\n\ndata: 2\n\n";
+        let mut reader = FramedRead::new(test_data.as_bytes(), codec);
+        let event = reader
+            .next()
+            .await
+            .expect("missing event")
+            .expect("failed to parse");
+        assert!(event == SseEvent::new().with_data("1"));
+        assert!(*calls.lock().unwrap() == 1);
+
+        let events: Vec<_> = reader
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+        assert!(events == [SseEvent::new().with_data("2")]);
+        assert!(*calls.lock().unwrap() == 1);
+    }
+
+    #[tokio::test]
+    async fn eof_flush() {
+        let test_data = "data: a\n\nevent: test\ndata: part1\ndata: part2";
+
+        // Per spec, the partial event is discarded.
+        let events: Vec<_> = FramedRead::new(test_data.as_bytes(), SseCodec::new())
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+        assert!(events == [SseEvent::new().with_data("a")]);
+
+        let codec = SseCodec::new().with_eof_flush(true);
+        let events: Vec<_> = FramedRead::new(test_data.as_bytes(), codec)
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+        let expected_events = [
+            SseEvent::new().with_data("a"),
+            SseEvent::new().with_event("test").with_data("part1\npart2"),
+        ];
+        assert!(events == expected_events);
+
+        // Newlines at the end of the stream, including a \r that may be the start of a \r\n.
+        for test_data in ["data: a\n", "data: a\r", "data: a\r\n"] {
+            let mut codec = SseCodec::new().with_eof_flush(true);
+            let mut buffer = BytesMut::from(test_data);
+            let event = codec
+                .decode_eof(&mut buffer)
+                .expect("failed to parse")
+                .expect("missing event");
+            assert!(event == SseEvent::new().with_data("a"));
+            assert!(codec
+                .decode_eof(&mut buffer)
+                .expect("failed to parse")
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn eof_terminated_line() {
+        // A terminated line and an unterminated line at eof are handled the same way:
+        // discarded per spec, or dispatched with the same data when flushing.
+        let test_data = [
+            ("data: x\n", "x"),
+            ("data: x", "x"),
+            ("data: x\ndata:\n", "x\n"),
+            ("data: x\ndata:", "x\n"),
+        ];
+        for (test_data, expected_data) in test_data {
+            for eof_flush in [false, true] {
+                let mut codec = SseCodec::new().with_eof_flush(eof_flush);
+                let mut buffer = BytesMut::from(test_data);
+                assert!(codec
+                    .decode(&mut buffer)
+                    .expect("failed to parse")
+                    .is_none());
+
+                let event = codec.decode_eof(&mut buffer).expect("failed to parse");
+                if eof_flush {
+                    assert!(event == Some(SseEvent::new().with_data(expected_data)));
+                } else {
+                    assert!(event.is_none());
+                }
+                assert!(codec
+                    .decode_eof(&mut buffer)
+                    .expect("failed to parse")
+                    .is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn decode_batch() {
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from(
+            &b"data: 1\n\ndata: 2\n\ndata: 3\n\ndata: 4\n\ndata: 5\n\ndata: \xFF\n\n"[..],
+        );
+
+        let (events, error) = codec.decode_batch(&mut buffer, 2);
+        let data: Vec<_> = events.iter().map(|event| event.data.as_deref()).collect();
+        assert!(data == [Some("1"), Some("2")]);
+        assert!(error.is_none());
+
+        let (events, error) = codec.decode_batch(&mut buffer, 5);
+        let data: Vec<_> = events.iter().map(|event| event.data.as_deref()).collect();
+        assert!(data == [Some("3"), Some("4"), Some("5")]);
+        assert!(matches!(error, Some(SseCodecError::InvalidUtf8 { .. })));
+    }
+
+    #[test]
+    fn decode_batch_error_not_reproduced() {
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from(&b"data: 1\n\ndata: \xFF\n\n"[..]);
+
+        let (events, error) = codec.decode_batch(&mut buffer, 5);
+        assert!(events == [SseEvent::new().with_data("1")]);
+        assert!(matches!(error, Some(SseCodecError::InvalidUtf8 { .. })));
+
+        // The bad bytes are dropped, like after a reconnect, so the error can't happen again.
+        buffer.clear();
+        buffer.extend_from_slice(b"data: 2\n\n");
+        let (events, error) = codec.decode_batch(&mut buffer, 5);
+        assert!(events == [SseEvent::new().with_data("2")]);
+        assert!(error.is_none());
+    }
+
+    #[test]
+    fn non_ascii_field_name() {
+        // Non-ascii field names are unknown, so they are ignored.
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from("évent: x\ndätä: y\n日本: z\nevent: a\ndata: b\n\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event == SseEvent::new().with_event("a").with_data("b"));
+
+        // Field names must still be valid utf8.
+        let mut buffer = BytesMut::from(&b"data: a\n\xE9vent: x\n\n"[..]);
+        let error = codec.decode(&mut buffer).expect_err("missing error");
+        assert!(matches!(error, SseCodecError::InvalidUtf8 { line: 8, .. }));
+    }
+
+    #[tokio::test]
+    async fn event_separator() {
+        let test_data = "event: a\ndata: 1\n;\n\ndata: 2\n\ndata: 3\r\n;\r\n";
+        let codec = SseCodec::new().with_event_separator(EventSeparator::Line(";".into()));
+        let events: Vec<_> = FramedRead::new(test_data.as_bytes(), codec)
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+
+        let expected_events = [
+            SseEvent::new().with_event("a").with_data("1"),
+            SseEvent::new().with_data("2\n3"),
+        ];
+        assert!(events == expected_events);
+
+        let mut codec = SseCodec::new()
+            .with_event_separator(EventSeparator::Line(";".into()))
+            .with_eof_flush(true);
+        let mut buffer = BytesMut::from("data: a\n\ndata: b");
+        let event = codec
+            .decode_eof(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event == SseEvent::new().with_data("a\nb"));
+    }
+
+    #[test]
+    fn try_from_str() {
+        let event = SseEvent::try_from("event: a\ndata: 1\ndata: 2\n\n").expect("failed to parse");
+        assert!(event == SseEvent::new().with_event("a").with_data("1\n2"));
+
+        let event = SseEvent::try_from(": comment\ndata: 1").expect("failed to parse");
+        assert!(event == SseEvent::new().with_data("1"));
+
+        for (block, expected_count) in [
+            ("data: 1\n\ndata: 2\n\n", 2),
+            ("data: 1\n\ndata: 2", 2),
+            ("", 0),
+            ("event: a\n\n", 0),
+        ] {
+            let error = SseEvent::try_from(block).expect_err("missing error");
+            assert!(matches!(
+                error,
+                SseCodecError::NotSingleEvent { count } if count == expected_count
+            ));
+        }
+    }
+
+    #[test]
+    fn decode_slice_split_crlf() {
+        let mut codec = SseCodec::new();
+
+        // Stop right after the \r.
+        let (event, consumed) = codec.decode_slice(b"data: a\r").expect("failed to parse");
+        assert!(event.is_none());
+        assert!(consumed == 8);
+        assert!(codec.last_newline_was_cr());
+
+        // The \n completes the \r\n newline, so it is not a blank line.
+        let input = b"\ndata: b\n\n";
+        let (event, consumed) = codec.decode_slice(input).expect("failed to parse");
+        assert!(event == Some(SseEvent::new().with_data("a\nb")));
+        assert!(consumed == input.len());
+        assert!(!codec.last_newline_was_cr());
+
+        // Unconsumed bytes are passed again.
+        let (event, consumed) = codec.decode_slice(b"data: c\n").expect("failed to parse");
+        assert!(event.is_none());
+        assert!(consumed == 8);
+        let (event, consumed) = codec.decode_slice(b"data: d").expect("failed to parse");
+        assert!(event.is_none());
+        assert!(consumed == 0);
+        let (event, _) = codec.decode_slice(b"data: d\n\n").expect("failed to parse");
+        assert!(event == Some(SseEvent::new().with_data("c\nd")));
+    }
+
+    #[test]
+    fn looks_like_sse() {
+        let sse_like: [&[u8]; 8] = [
+            b"data: hello\n\n",
+            b"event:a\ndata: b",
+            b"id\n",
+            b"retry: 1000\r\n",
+            b": comment",
+            b"\xEF\xBB\xBFdata: a",
+            b"\r\n\ndata: a",
+            b"data:",
+        ];
+        for prefix in sse_like {
+            assert!(SseCodec::looks_like_sse(prefix), "{prefix:?}");
+        }
+
+        let not_sse_like: [&[u8]; 8] = [
+            b"",
+            b"\n\n",
+            b"<!DOCTYPE html>",
+            b"{\"data\": 1}",
+            b"HTTP/1.1 200 OK\r\n",
+            b"database: x\n",
+            b" data: a\n",
+            b"data",
+        ];
+        for prefix in not_sse_like {
+            assert!(!SseCodec::looks_like_sse(prefix), "{prefix:?}");
+        }
+    }
+
+    #[test]
+    fn id_history() {
+        let mut codec = SseCodec::new().with_id_history(3);
+        let mut buffer = BytesMut::from("id: 1\ndata: a\n\nid: 2\ndata: b\n\ndata: c\n\nid: 3\n\n");
+        while codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_some()
+        {}
+        assert!(*codec.id_history() == ["1", "2", "3"]);
+
+        // The history is kept across reconnects, and the oldest ids are evicted.
+        let mut codec = codec.new_with_continuation();
+        let mut buffer = BytesMut::from("id: 4\ndata: d\n\nid: 5\ndata: e\n\n");
+        while codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_some()
+        {}
+        assert!(*codec.id_history() == ["3", "4", "5"]);
+
+        codec.reset();
+        assert!(*codec.id_history() == ["3", "4", "5"]);
+        codec.reset_all();
+        assert!(codec.id_history().is_empty());
+
+        // By default, no history is kept.
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from("id: 1\ndata: a\n\n");
+        codec.decode(&mut buffer).expect("failed to parse");
+        assert!(codec.id_history().is_empty());
+    }
+
+    #[test]
+    fn decode_after_eof() {
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from("data: a\n\nevent: b\ndata: partial\ndata: part");
+
+        let event = codec
+            .decode_eof(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("a"));
+
+        // The partial event is discarded, and nothing is emitted after the end of the stream.
+        for _ in 0..2 {
+            assert!(codec
+                .decode_eof(&mut buffer)
+                .expect("failed to parse")
+                .is_none());
+            assert!(buffer.is_empty());
+            assert!(codec
+                .decode(&mut buffer)
+                .expect("failed to parse")
+                .is_none());
+        }
+
+        // The discarded event does not leak into later data.
+        let mut buffer = BytesMut::from("\ndata: c\n\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event == SseEvent::new().with_data("c"));
+    }
+
+    #[tokio::test]
+    async fn max_lines_per_poll() {
+        let mut test_data = ": comment\n".repeat(1000);
+        test_data.push_str("data: a\n\n");
+
+        let mut codec = SseCodec::new().with_max_lines_per_poll(100);
+        let mut buffer = BytesMut::from(test_data.as_str());
+        let mut polls = 1;
+        let event = loop {
+            match codec.decode(&mut buffer).expect("failed to parse") {
+                Some(event) => break event,
+                None => polls += 1,
+            }
+        };
+        assert!(event.data.as_deref() == Some("a"));
+        assert!(polls == 11);
+        assert!(buffer.is_empty());
+
+        // The end of the stream is not limited.
+        let codec = SseCodec::new().with_max_lines_per_poll(100);
+        let events: Vec<_> = FramedRead::new(test_data.as_bytes(), codec)
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+        assert!(events == [SseEvent::new().with_data("a")]);
+    }
+
+    #[tokio::test]
+    async fn eof_error() {
+        let test_data = "data: a\n\n: comment\nevent: b\ndata: partial\ndata: part";
+        let codec = SseCodec::new().with_eof_error(true);
+        let items: Vec<_> = FramedRead::new(test_data.as_bytes(), codec).collect().await;
+
+        assert!(items.len() == 2);
+        assert!(items[0].as_ref().expect("failed to parse").data.as_deref() == Some("a"));
+        assert!(matches!(
+            items[1],
+            Err(SseCodecError::UnterminatedEvent {
+                bytes_discarded: 43
+            })
+        ));
+
+        // A complete stream, or one ending in comments, is not an error.
+        for test_data in ["data: a\n\n", "data: a\n\n: comment\n", ""] {
+            let codec = SseCodec::new().with_eof_error(true);
+            let items: Vec<_> = FramedRead::new(test_data.as_bytes(), codec).collect().await;
+            assert!(items.iter().all(Result::is_ok));
+        }
+    }
+
+    #[tokio::test]
+    async fn cr_only() {
+        let test_data =
+            "event: a\rdata: b\r\r: comment\rdata: c\rdata: d\r\r\r\rid: 1\rdata: e\r\r";
+        let expected_events = [
+            SseEvent::new().with_event("a").with_data("b"),
+            SseEvent::new().with_data("c\nd"),
+            SseEvent::new().with_id("1").with_data("e"),
+        ];
+
+        let events: Vec<_> = FramedRead::new(test_data.as_bytes(), SseCodec::new())
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+        assert!(events == expected_events);
+
+        // Splitting after every \r must not change anything.
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::new();
+        let mut events = Vec::new();
+        let mut seen_cr = false;
+        for b in test_data.bytes() {
+            buffer.extend_from_slice(&[b]);
+            while let Some(event) = codec.decode(&mut buffer).expect("failed to parse") {
+                events.push(event);
+            }
+
+            // Each \r completes a line right away, without waiting for a possible \n.
+            seen_cr |= b == b'\r';
+            assert!(codec.last_newline_was_cr() == seen_cr);
+            assert!(b != b'\r' || buffer.is_empty());
+        }
+        assert!(events == expected_events);
+
+        // An unterminated event at the end of the stream is discarded, per spec.
+        let test_data = "data: a\r\rdata: b\r";
+        let events: Vec<_> = FramedRead::new(test_data.as_bytes(), SseCodec::new())
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+        assert!(events == [SseEvent::new().with_data("a")]);
+
+        let codec = SseCodec::new().with_eof_flush(true);
+        let events: Vec<_> = FramedRead::new(test_data.as_bytes(), codec)
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+        assert!(
+            events
+                == [
+                    SseEvent::new().with_data("a"),
+                    SseEvent::new().with_data("b")
+                ]
+        );
+    }
+
+    #[test]
+    fn next_reconnect_delay() {
+        let mut codec = SseCodec::new();
+        assert!(codec.next_reconnect_delay() == Duration::from_secs(3));
+
+        let mut buffer = BytesMut::from("retry: 1500\n\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(codec.next_reconnect_delay() == Duration::from_millis(1500));
+
+        let mut codec = SseCodec::new()
+            .with_default_reconnect_delay(Duration::from_secs(1))
+            .with_max_reconnect_delay(Duration::from_secs(10));
+        assert!(codec.next_reconnect_delay() == Duration::from_secs(1));
+
+        let mut buffer = BytesMut::from("retry: 60000\n\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(codec.next_reconnect_delay() == Duration::from_secs(10));
+    }
+
+    #[test]
+    fn zero_retry() {
+        // A retry of 0 is honored, but clamped to the minimum delay instead of reconnecting in a tight loop.
+        let test_data = [
+            (SseCodec::new(), Duration::from_millis(100)),
+            (
+                SseCodec::new().with_min_reconnect_delay(Duration::from_millis(20)),
+                Duration::from_millis(20),
+            ),
+            (
+                SseCodec::new().with_min_reconnect_delay(Duration::ZERO),
+                Duration::ZERO,
+            ),
+        ];
+        for (mut codec, expected) in test_data {
+            let mut buffer = BytesMut::from("retry: 0\n\n");
+            assert!(codec
+                .decode(&mut buffer)
+                .expect("failed to parse")
+                .is_none());
+            assert!(codec.last_retry() == Some(0));
+            assert!(codec.next_reconnect_delay() == expected);
+        }
+
+        // Without a minimum, small nonzero retries are honored as is.
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from("retry: 50\n\n");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(codec.next_reconnect_delay() == Duration::from_millis(50));
+    }
+
+    #[test]
+    fn clear_last_event_id() {
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from("id: 1\ndata: a\n\nevent: b\ndata: partial\n");
+        codec.decode(&mut buffer).expect("failed to parse");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(codec.last_event_id() == Some("1"));
+
+        codec.clear_last_event_id();
+        assert!(codec.last_event_id().is_none());
+
+        // The partial event is untouched.
+        let mut buffer = BytesMut::from("data: c\n\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event == SseEvent::new().with_event("b").with_data("partial\nc"));
+        assert!(codec.last_event_id().is_none());
+    }
+
+    #[tokio::test]
+    async fn stats_handle() {
+        assert!(SseCodec::new().stats_handle().is_none());
+
+        let mut codec = SseCodec::new().with_stats();
+        let handle = codec.stats_handle().expect("missing stats handle");
+
+        let test_data = "data: a\r\n: comment\r\n\r\ndata: partial\n";
+        let mut buffer = BytesMut::from(test_data);
+        while codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_some()
+        {}
+
+        // Continuations share the stats.
+        let mut codec = codec.new_with_continuation();
+        let mut buffer = BytesMut::from("data: b\n\n");
+        codec.decode(&mut buffer).expect("failed to parse");
+
+        let (bytes, events) = tokio::spawn(async move { (handle.bytes(), handle.events()) })
+            .await
+            .expect("task failed");
+        assert!(bytes == (test_data.len() + "data: b\n\n".len()) as u64);
+        assert!(events == 2);
     }
 }
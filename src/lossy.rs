@@ -0,0 +1,215 @@
+use crate::DecodeProgress;
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use tokio_util::bytes::Buf;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// An sse event with data that may not be valid utf8
+#[derive(Debug, Default, PartialEq)]
+pub struct SseBytesEvent {
+    /// The event field
+    pub event: Option<String>,
+
+    /// The raw bytes of the data field
+    pub data: Option<Vec<u8>>,
+
+    /// The id field
+    pub id: Option<String>,
+
+    /// The retry field
+    pub retry: Option<u64>,
+}
+
+/// An sse codec that keeps the raw bytes of data fields, without validating them as utf8.
+///
+/// This tolerates servers that send binary data in data fields.
+/// All other lines, including the names of data fields, must still be valid utf8.
+/// Make one with [`SseCodec::with_lossy_data`].
+#[derive(Debug, Default)]
+pub struct LossyDataSseCodec {
+    codec: SseCodec,
+
+    /// The data of the current event.
+    data: Option<Vec<u8>>,
+}
+
+impl SseCodec {
+    /// Wrap this codec, so that data fields are yielded as raw bytes in [`SseBytesEvent`]s.
+    pub fn with_lossy_data(self) -> LossyDataSseCodec {
+        LossyDataSseCodec {
+            codec: self,
+            data: None,
+        }
+    }
+}
+
+impl LossyDataSseCodec {
+    /// Decode at most one line, keeping the raw bytes of data fields.
+    fn decode_line(&mut self, bytes: &mut BytesMut) -> Result<DecodeProgress, SseCodecError> {
+        let data = self.data.get_or_insert_with(Vec::new);
+        let (progress, consumed) = self
+            .codec
+            .parser
+            .decode_line_slice_with(bytes, Some(data))?;
+        bytes.advance(consumed);
+
+        Ok(progress)
+    }
+
+    /// Attach the raw data to an event dispatched by the codec.
+    fn finish_event(&mut self, event: SseEvent) -> SseBytesEvent {
+        let data = self.data.take().filter(|_| event.data.is_some());
+
+        SseBytesEvent {
+            event: event.event,
+            data: data.map(|mut data| {
+                // Trim trailing \n, per-spec.
+                if data.last() == Some(&b'\n') {
+                    data.pop();
+                }
+                data
+            }),
+            id: event.id,
+            retry: event.retry,
+        }
+    }
+}
+
+impl Decoder for LossyDataSseCodec {
+    type Item = SseBytesEvent;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            match self.decode_line(bytes)? {
+                DecodeProgress::Field
+                | DecodeProgress::Comment
+                | DecodeProgress::SkippedInvalidUtf8 => {}
+                DecodeProgress::BlankLine => {
+                    // The event was not dispatched.
+                    if self.codec.parser.data.is_none() {
+                        self.data = None;
+                    }
+                }
+                DecodeProgress::Event(event) => return Ok(Some(self.finish_event(event))),
+                DecodeProgress::Blocked => return Ok(None),
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(item) = self.decode(bytes)? {
+            return Ok(Some(item));
+        }
+
+        // Complete a partial data line here, since the codec would validate it.
+        if self.codec.parser.config.eof_flush && !bytes.is_empty() {
+            bytes.extend_from_slice(b"\n");
+            if let Some(item) = self.decode(bytes)? {
+                return Ok(Some(item));
+            }
+        }
+
+        match self.codec.decode_eof(bytes)? {
+            Some(event) => Ok(Some(self.finish_event(event))),
+            None => {
+                self.data = None;
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    #[tokio::test]
+    async fn lossy_data() {
+        let test_data =
+            &b"event: bin\ndata: \x00\xFF\xFE\ndata:ok\n\ndata\nid: 1\n\ndata: \xC3\n"[..];
+        let events: Vec<_> = FramedRead::new(test_data, SseCodec::new().with_lossy_data())
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+
+        let expected = [
+            SseBytesEvent {
+                event: Some("bin".into()),
+                data: Some(b"\x00\xFF\xFE\nok".to_vec()),
+                id: None,
+                retry: None,
+            },
+            SseBytesEvent {
+                event: None,
+                data: Some(Vec::new()),
+                id: Some("1".into()),
+                retry: None,
+            },
+        ];
+        assert!(events == expected);
+
+        // Field names must still be valid utf8.
+        let mut buffer = BytesMut::from(&b"dat\xFF: a\n\n"[..]);
+        let error = SseCodec::new()
+            .with_lossy_data()
+            .decode(&mut buffer)
+            .expect_err("missing error");
+        assert!(matches!(error, SseCodecError::InvalidUtf8 { line: 1, .. }));
+    }
+
+    #[test]
+    fn lossy_data_field_handling() {
+        // Data fields are found through aliases, and trimmed like other fields.
+        let mut codec = SseCodec::new()
+            .with_field_aliases([("d", "data")])
+            .with_field_trim("data", false)
+            .with_utf8_recovery(true)
+            .with_lossy_data();
+        let mut buffer = BytesMut::from(&b"d: \xFF\nda\xFFta: x\nevent: \xFF\ndata:b\n\n"[..]);
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+
+        // Lines with invalid field names are skipped by utf8 recovery.
+        assert!(
+            event
+                == SseBytesEvent {
+                    event: None,
+                    data: Some(b" \xFF\nb".to_vec()),
+                    id: None,
+                    retry: None,
+                }
+        );
+
+        // The codec still tracks the data, with invalid utf8 replaced.
+        let mut buffer = BytesMut::from(&b"d: \xFF\n"[..]);
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        let view = codec.codec.in_progress();
+        assert!(view.data == Some(" \u{FFFD}"));
+        assert!(view.data_len == Some(4));
+    }
+
+    #[test]
+    fn lossy_data_eof_flush() {
+        let mut codec = SseCodec::new().with_eof_flush(true).with_lossy_data();
+        let mut buffer = BytesMut::from(&b"data: a\ndata: \xFF"[..]);
+        let event = codec
+            .decode_eof(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some(&b"a\n\xFF"[..]));
+        assert!(codec
+            .decode_eof(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+    }
+}
@@ -0,0 +1,34 @@
+use crate::SseEvent;
+
+/// A transformation applied to each event, such as rewriting ids or redacting fields
+///
+/// Middlewares can be chained on a stream with [`stream::apply_middleware`](crate::stream::apply_middleware).
+/// Closures taking and returning an event implement this trait.
+pub trait SseMiddleware {
+    /// Transform an event.
+    ///
+    /// Returns `None` to drop the event.
+    fn transform(&mut self, event: SseEvent) -> Option<SseEvent>;
+}
+
+impl<F> SseMiddleware for F
+where
+    F: FnMut(SseEvent) -> Option<SseEvent>,
+{
+    fn transform(&mut self, event: SseEvent) -> Option<SseEvent> {
+        self(event)
+    }
+}
+
+/// A middleware that removes the id field of events
+///
+/// This is useful for proxies that should not expose the ids of the upstream server.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StripId;
+
+impl SseMiddleware for StripId {
+    fn transform(&mut self, mut event: SseEvent) -> Option<SseEvent> {
+        event.id = None;
+        Some(event)
+    }
+}
@@ -0,0 +1,260 @@
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use tokio_util::bytes::Buf;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// The state of the multipart parser
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MultipartState {
+    /// Looking for a boundary line, skipping the preamble.
+    Boundary,
+
+    /// Reading the headers of a part.
+    Headers,
+
+    /// Reading the body of a part.
+    Body,
+
+    /// The closing boundary was seen, so the epilogue is skipped.
+    End,
+}
+
+/// An sse codec for SSE streams embedded in a `multipart/mixed` body.
+///
+/// This is not part of the SSE spec.
+/// Boundaries and part headers are stripped,
+/// and the bodies of parts with a `text/event-stream` content type are decoded as a single SSE stream.
+/// Other parts are skipped.
+/// Both `\r\n` and `\n` are accepted as newlines in the multipart framing.
+#[derive(Debug)]
+pub struct MultipartSseCodec {
+    /// The inner codec
+    codec: SseCodec,
+
+    /// The stripped SSE bytes
+    body: BytesMut,
+
+    /// The boundary, without the leading dashes.
+    boundary: String,
+
+    /// The delimiter that ends a part body, a newline followed by the dashes and the boundary.
+    delimiter: Vec<u8>,
+
+    /// The parser state
+    state: MultipartState,
+
+    /// Whether the current part is an SSE stream.
+    is_event_stream: bool,
+}
+
+impl MultipartSseCodec {
+    /// Make a new multipart SSE Event decoder, using the given boundary.
+    pub fn new(boundary: impl Into<String>) -> Self {
+        Self::with_codec(SseCodec::new(), boundary)
+    }
+
+    /// Make a new multipart SSE Event decoder, using the given codec for the SSE parts.
+    pub fn with_codec(codec: SseCodec, boundary: impl Into<String>) -> Self {
+        let boundary = boundary.into();
+        let delimiter = format!("\n--{boundary}").into_bytes();
+
+        Self {
+            codec,
+            body: BytesMut::new(),
+            boundary,
+            delimiter,
+            state: MultipartState::Boundary,
+            is_event_stream: false,
+        }
+    }
+
+    /// Take the next line of the multipart framing, without its newline.
+    fn next_line(bytes: &mut BytesMut) -> Option<BytesMut> {
+        let newline_index = bytes.iter().position(|b| *b == b'\n')?;
+        let mut line = bytes.split_to(newline_index + 1);
+        line.truncate(newline_index);
+        if line.ends_with(b"\r") {
+            line.truncate(newline_index - 1);
+        }
+
+        Some(line)
+    }
+
+    /// Add bytes from the body of a part.
+    fn push_body(&mut self, bytes: &[u8]) {
+        if self.is_event_stream {
+            self.body.extend_from_slice(bytes);
+        }
+    }
+}
+
+impl Decoder for MultipartSseCodec {
+    type Item = SseEvent;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            if let Some(event) = self.codec.decode(&mut self.body)? {
+                return Ok(Some(event));
+            }
+
+            match self.state {
+                MultipartState::Boundary => {
+                    let line = match Self::next_line(bytes) {
+                        Some(line) => line,
+                        None => return Ok(None),
+                    };
+
+                    // Transport padding after the boundary is allowed.
+                    let boundary = line
+                        .strip_prefix(b"--")
+                        .and_then(|line| line.strip_prefix(self.boundary.as_bytes()))
+                        .map(|rest| rest.trim_ascii_end());
+                    match boundary {
+                        Some(b"") => {
+                            self.state = MultipartState::Headers;
+                            self.is_event_stream = false;
+                        }
+                        Some(b"--") => self.state = MultipartState::End,
+                        _ => {}
+                    }
+                }
+                MultipartState::Headers => {
+                    let line = match Self::next_line(bytes) {
+                        Some(line) => line,
+                        None => return Ok(None),
+                    };
+
+                    if line.is_empty() {
+                        self.state = MultipartState::Body;
+                        continue;
+                    }
+
+                    let line = String::from_utf8_lossy(&line);
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.trim().eq_ignore_ascii_case("content-type") {
+                            self.is_event_stream = value.split(';').next().is_some_and(|mime| {
+                                mime.trim().eq_ignore_ascii_case("text/event-stream")
+                            });
+                        }
+                    }
+                }
+                MultipartState::Body => {
+                    let delimiter_index = bytes
+                        .windows(self.delimiter.len())
+                        .position(|window| window == self.delimiter);
+
+                    match delimiter_index {
+                        Some(delimiter_index) => {
+                            // The newline before the boundary belongs to the delimiter.
+                            let body_end =
+                                if delimiter_index > 0 && bytes[delimiter_index - 1] == b'\r' {
+                                    delimiter_index - 1
+                                } else {
+                                    delimiter_index
+                                };
+                            let body = bytes.split_to(body_end);
+                            self.push_body(&body);
+
+                            // Skip the rest of the newline, so the boundary line is next.
+                            bytes.advance(delimiter_index + 1 - body_end);
+                            self.state = MultipartState::Boundary;
+                        }
+                        None => {
+                            // Keep enough bytes to find a delimiter that is split across reads.
+                            let mut n = bytes.len().saturating_sub(self.delimiter.len());
+                            if n > 0 && bytes[n - 1] == b'\r' {
+                                n -= 1;
+                            }
+                            if n == 0 {
+                                return Ok(None);
+                            }
+
+                            let body = bytes.split_to(n);
+                            self.push_body(&body);
+                        }
+                    }
+                }
+                MultipartState::End => {
+                    bytes.clear();
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(event) = self.decode(bytes)? {
+            return Ok(Some(event));
+        }
+
+        // The stream ended without a closing boundary, so the rest of the part body is still SSE bytes.
+        if self.state == MultipartState::Body {
+            let body = bytes.split();
+            self.push_body(&body);
+        }
+
+        self.codec.decode_eof(&mut self.body)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    const TEST_DATA: &str = concat!(
+        "preamble\r\n",
+        "--abc\r\n",
+        "Content-Type: application/json\r\n",
+        "\r\n",
+        "{\"data\": 1}\r\n",
+        "--abc  \r\n",
+        "content-type: text/event-stream; charset=utf-8\r\n",
+        "\r\n",
+        "event: a\r\ndata: 1\r\n\r\ndata: 2\n\n",
+        "\r\n--abc\n",
+        "\n",
+        "data: not sse\n\n",
+        "\n--abc--\r\n",
+        "data: epilogue\n\n",
+    );
+
+    fn expected_events() -> [SseEvent; 2] {
+        [
+            SseEvent::new().with_event("a").with_data("1"),
+            SseEvent::new().with_data("2"),
+        ]
+    }
+
+    #[tokio::test]
+    async fn multipart() {
+        let reader = FramedRead::new(TEST_DATA.as_bytes(), MultipartSseCodec::new("abc"));
+        let events: Vec<_> = reader
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+        assert!(events == expected_events());
+    }
+
+    #[test]
+    fn multipart_split_reads() {
+        let mut codec = MultipartSseCodec::new("abc");
+        let mut buffer = BytesMut::new();
+        let mut events = Vec::new();
+        for b in TEST_DATA.bytes() {
+            buffer.extend_from_slice(&[b]);
+            while let Some(event) = codec.decode(&mut buffer).expect("failed to parse") {
+                events.push(event);
+            }
+        }
+        while let Some(event) = codec.decode_eof(&mut buffer).expect("failed to parse") {
+            events.push(event);
+        }
+
+        assert!(events == expected_events());
+    }
+}
@@ -0,0 +1,117 @@
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// An item yielded by codecs that report more than whole events
+#[derive(Debug, PartialEq)]
+pub enum SseStreamItem {
+    /// The first bytes of the stream were read, like the browser `open` event.
+    ///
+    /// This is only yielded by [`OpenSignalSseCodec`].
+    Open,
+
+    /// An event.
+    Event(SseEvent),
+
+    /// A retry field was parsed, even if no event follows.
+    ///
+    /// This is only yielded by [`RetryUpdateSseCodec`](crate::RetryUpdateSseCodec).
+    RetryUpdate(u64),
+
+    /// A data line of the current event.
+    ///
+    /// This is only yielded by [`DataChunkSseCodec`](crate::DataChunkSseCodec).
+    DataChunk {
+        /// The event field of the current event, if it was sent before this line.
+        event: Option<String>,
+
+        /// The value of the data line, without a newline.
+        chunk: String,
+    },
+
+    /// The current event is complete.
+    ///
+    /// This holds the other fields of the event, with no data.
+    /// This is only yielded by [`DataChunkSseCodec`](crate::DataChunkSseCodec).
+    EventEnd(SseEvent),
+}
+
+/// An sse codec that yields [`SseStreamItem::Open`] once the first bytes are read.
+///
+/// Make one with [`SseCodec::with_open_signal`].
+#[derive(Debug, Default)]
+pub struct OpenSignalSseCodec {
+    codec: SseCodec,
+    opened: bool,
+}
+
+impl SseCodec {
+    /// Wrap this codec, so that it yields [`SseStreamItem::Open`] before any events.
+    ///
+    /// The open item is yielded as soon as the first bytes are read,
+    /// so it can be used to tell when the connection is working, before the first event arrives.
+    pub fn with_open_signal(self) -> OpenSignalSseCodec {
+        OpenSignalSseCodec {
+            codec: self,
+            opened: false,
+        }
+    }
+}
+
+impl Decoder for OpenSignalSseCodec {
+    type Item = SseStreamItem;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if !self.opened && !bytes.is_empty() {
+            self.opened = true;
+            return Ok(Some(SseStreamItem::Open));
+        }
+
+        let event = self.codec.decode(bytes)?;
+        Ok(event.map(SseStreamItem::Event))
+    }
+
+    fn decode_eof(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if !self.opened && !bytes.is_empty() {
+            self.opened = true;
+            return Ok(Some(SseStreamItem::Open));
+        }
+
+        let event = self.codec.decode_eof(bytes)?;
+        Ok(event.map(SseStreamItem::Event))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    #[tokio::test]
+    async fn open_comes_first() {
+        let test_data = ": connected\n\ndata: a\n\ndata: b\n\n";
+        let reader = FramedRead::new(test_data.as_bytes(), SseCodec::new().with_open_signal());
+        let items: Vec<_> = reader
+            .map(|item| item.expect("failed to parse"))
+            .collect()
+            .await;
+
+        let expected_items = [
+            SseStreamItem::Open,
+            SseStreamItem::Event(SseEvent::new().with_data("a")),
+            SseStreamItem::Event(SseEvent::new().with_data("b")),
+        ];
+        assert!(items == expected_items);
+    }
+
+    #[tokio::test]
+    async fn no_open_for_empty_stream() {
+        let reader = FramedRead::new(&b""[..], SseCodec::new().with_open_signal());
+        let items: Vec<_> = reader.collect().await;
+        assert!(items.is_empty());
+    }
+}
@@ -0,0 +1,902 @@
+use crate::split_field;
+use crate::trim_value;
+use crate::BlankLinePolicy;
+use crate::DecodeProgress;
+use crate::EventSeparator;
+use crate::FirstEventHook;
+use crate::Line;
+use crate::LineEnding;
+use crate::LineEndingInspector;
+use crate::LineInspector;
+use crate::LineScanner;
+use crate::SpareBuffers;
+use crate::SseCodec;
+use crate::SseCodecConfig;
+use crate::SseCodecError;
+use crate::SseEvent;
+use crate::SseStats;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// A sans-io sse parser.
+///
+/// This holds the line and field state machine used by [`SseCodec`], without depending on any runtime.
+/// It owns its input buffer, so it can be driven by any runtime, or synchronously.
+/// Push bytes with [`SseParser::push`], then call [`SseParser::next_event`] until it returns `None`.
+#[derive(Debug)]
+pub struct SseParser {
+    /// The line scanner
+    pub(crate) lines: LineScanner,
+
+    /// The event field
+    pub(crate) event: Option<String>,
+
+    /// The data field
+    pub(crate) data: Option<String>,
+
+    /// The id field
+    pub(crate) id: Option<String>,
+
+    /// The retry field
+    pub(crate) retry: Option<u64>,
+
+    /// The id of the last dispatched event that had one.
+    pub(crate) last_event_id: Option<String>,
+
+    /// The last retry value, even if it was not part of a dispatched event.
+    pub(crate) last_retry: Option<u64>,
+
+    /// A retry value that was parsed but not yet reported by [`crate::RetryUpdateSseCodec`].
+    pub(crate) retry_update: Option<u64>,
+
+    /// The configuration.
+    pub(crate) config: SseCodecConfig,
+
+    /// Consumed bytes, if raw capture is enabled.
+    pub(crate) raw: Option<Vec<u8>>,
+
+    /// The number of bytes consumed since the last event was completed.
+    pub(crate) event_bytes: usize,
+
+    /// The number of bytes consumed from the stream.
+    pub(crate) stream_bytes: usize,
+
+    /// Counters for bytes and events, if enabled.
+    pub(crate) stats: Option<Arc<SseStats>>,
+
+    /// The most recent ids, oldest first.
+    pub(crate) id_history: VecDeque<String>,
+
+    /// A callback invoked for each complete line, for debugging.
+    pub(crate) line_inspector: Option<LineInspector>,
+
+    /// A callback invoked when the first event is dispatched.
+    pub(crate) first_event_hook: Option<FirstEventHook>,
+
+    /// A callback invoked with the newline of each complete line, for debugging.
+    pub(crate) line_ending_inspector: Option<LineEndingInspector>,
+
+    /// Whether the last line ended with a \r at the end of a buffer, which is not reported yet.
+    pub(crate) line_ending_pending_cr: bool,
+
+    /// Empty buffers to reuse for fields, to avoid allocating.
+    pub(crate) spare: SpareBuffers,
+
+    /// The unparsed input pushed with [`SseParser::push`].
+    buffer: Vec<u8>,
+
+    /// Whether the end of the input was reached.
+    finished: bool,
+}
+
+impl SseParser {
+    /// Make a new parser.
+    pub fn new() -> Self {
+        Self {
+            lines: LineScanner::default(),
+            event: None,
+            data: None,
+            id: None,
+            retry: None,
+            last_event_id: None,
+            last_retry: None,
+            retry_update: None,
+            config: SseCodecConfig::default(),
+            raw: None,
+            event_bytes: 0,
+            stream_bytes: 0,
+            stats: None,
+            id_history: VecDeque::new(),
+            line_inspector: None,
+            first_event_hook: None,
+            line_ending_inspector: None,
+            line_ending_pending_cr: false,
+            spare: SpareBuffers::default(),
+            buffer: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// Make a new parser from a configured codec.
+    ///
+    /// The parser takes over the state of the codec.
+    pub fn with_codec(codec: SseCodec) -> Self {
+        codec.parser
+    }
+
+    /// Make a new parser for a new connection, continuing from this one.
+    ///
+    /// See [`SseCodec::new_with_continuation`].
+    pub(crate) fn new_with_continuation(&self) -> Self {
+        Self {
+            last_event_id: self.last_event_id.clone(),
+            last_retry: self.last_retry,
+            config: self.config.clone(),
+            raw: self.raw.as_ref().map(|_| Vec::new()),
+            stats: self.stats.clone(),
+            id_history: self.id_history.clone(),
+            line_inspector: self.line_inspector.clone(),
+            first_event_hook: self.first_event_hook.clone(),
+            line_ending_inspector: self.line_ending_inspector.clone(),
+            ..Self::new()
+        }
+    }
+
+    /// Add bytes to the input.
+    pub fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Mark the end of the input.
+    ///
+    /// After this, [`SseParser::next_event`] handles the rest of the input like the end of a stream,
+    /// discarding or flushing a partial event.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Parse the next event from the input.
+    ///
+    /// This returns `None` if more input is needed.
+    pub fn next_event(&mut self) -> Result<Option<SseEvent>, SseCodecError> {
+        if !self.finished {
+            return self.parse_buffered();
+        }
+
+        // A trailing \r can no longer be followed by a \n.
+        if self.buffer.is_empty() {
+            self.resolve_pending_cr(false);
+        }
+
+        if let Some(event) = self.parse_buffered()? {
+            return Ok(Some(event));
+        }
+
+        if self.needs_eof_flush(&self.buffer) {
+            let terminator = self.eof_terminator();
+            self.buffer.extend_from_slice(&terminator);
+            return self.parse_buffered();
+        }
+
+        let buffer = std::mem::take(&mut self.buffer);
+        self.discard_at_eof(&buffer).map(|()| None)
+    }
+
+    /// Get the id of the last dispatched event that had one.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// Get the unparsed input.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Parse lines from the input until an event is completed or more input is needed.
+    fn parse_buffered(&mut self) -> Result<Option<SseEvent>, SseCodecError> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        let result = self.decode_slice(&buffer);
+        let consumed = result.as_ref().map_or(0, |(_, consumed)| *consumed);
+        buffer.drain(..consumed);
+        self.buffer = buffer;
+
+        result.map(|(event, _)| event)
+    }
+
+    /// Decode at most one event from a slice.
+    ///
+    /// Returns the event, if one was completed, and the number of bytes consumed.
+    pub(crate) fn decode_slice(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<(Option<SseEvent>, usize), SseCodecError> {
+        let mut consumed = 0;
+        loop {
+            let (progress, n) = self.decode_line_slice(&bytes[consumed..])?;
+            consumed += n;
+            match progress {
+                DecodeProgress::Field
+                | DecodeProgress::Comment
+                | DecodeProgress::SkippedInvalidUtf8
+                | DecodeProgress::BlankLine => {}
+                DecodeProgress::Event(event) => return Ok((Some(event), consumed)),
+                DecodeProgress::Blocked => return Ok((None, consumed)),
+            }
+        }
+    }
+
+    /// Check if a partial event should be flushed at the end of the input.
+    ///
+    /// See [`SseCodec::with_eof_flush`].
+    pub(crate) fn needs_eof_flush(&self, bytes: &[u8]) -> bool {
+        self.config.eof_flush && (self.data.is_some() || self.lines.partial_line_len(bytes) > 0)
+    }
+
+    /// Get the bytes that terminate the partial line, if any, and then the event.
+    ///
+    /// If the last newline was a \r, the first \n is skipped as part of a \r\n newline.
+    pub(crate) fn eof_terminator(&self) -> Vec<u8> {
+        let mut terminator = b"\n".to_vec();
+        if let EventSeparator::Line(separator) = &self.config.event_separator {
+            terminator.extend_from_slice(separator.as_bytes());
+        }
+        terminator.extend_from_slice(b"\n");
+        terminator
+    }
+
+    /// Discard the partial event at the end of the input, including the unparsed bytes.
+    ///
+    /// Per-spec, buffered event parts should be discarded if the stream is terminated without a trailing newline.
+    pub(crate) fn discard_at_eof(&mut self, bytes: &[u8]) -> Result<(), SseCodecError> {
+        let partial_line_len = self.lines.partial_line_len(bytes);
+        let has_fields = self.event.is_some()
+            || self.data.is_some()
+            || self.id.is_some()
+            || self.retry.is_some();
+        let bytes_discarded = self.event_bytes + partial_line_len;
+        self.event_bytes = 0;
+
+        self.lines.discard_partial();
+        self.discard_event();
+        if let Some(raw) = self.raw.as_mut() {
+            raw.clear();
+        }
+
+        #[cfg(feature = "tracing")]
+        if has_fields || partial_line_len > 0 {
+            tracing::debug!(bytes_discarded, "discarded unterminated event at eof");
+        }
+
+        if self.config.eof_error && (has_fields || partial_line_len > 0) {
+            return Err(SseCodecError::UnterminatedEvent { bytes_discarded });
+        }
+
+        Ok(())
+    }
+
+    /// Discard the fields of the partially decoded event, keeping their buffers for reuse.
+    pub(crate) fn discard_event(&mut self) {
+        let fields = [
+            (&mut self.event, &mut self.spare.event),
+            (&mut self.data, &mut self.spare.data),
+            (&mut self.id, &mut self.spare.id),
+        ];
+        for (field, spare) in fields {
+            if let Some(mut value) = field.take() {
+                value.clear();
+                *spare = value;
+            }
+        }
+        self.retry = None;
+    }
+
+    /// Check that consuming `len` more bytes does not exceed the stream limit.
+    pub(crate) fn check_stream_bytes(&self, len: usize) -> Result<(), SseCodecError> {
+        match self.config.max_stream_bytes {
+            Some(limit) if self.stream_bytes.saturating_add(len) > limit => {
+                Err(SseCodecError::StreamTooLarge { limit })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Consume a line, recording the consumed bytes if raw capture is enabled.
+    pub(crate) fn consume_line(&mut self, bytes: &[u8], line: &Line) {
+        self.resolve_pending_cr(line.start > 0);
+        if let Some(line_ending_inspector) = self.line_ending_inspector.as_ref() {
+            match line.line_ending(bytes) {
+                Some(line_ending) => line_ending_inspector.inspect(line_ending),
+                None => self.line_ending_pending_cr = true,
+            }
+        }
+        if let Some(raw) = self.raw.as_mut() {
+            raw.extend_from_slice(&bytes[line.start..line.advance]);
+        }
+        self.event_bytes += line.advance - line.start;
+        self.stream_bytes += line.advance;
+        if let Some(stats) = self.stats.as_ref() {
+            stats.add_bytes(line.advance);
+        }
+        self.lines.consume(line);
+    }
+
+    /// Report the newline of a line that ended with a \r at the end of a buffer, once it is known.
+    pub(crate) fn resolve_pending_cr(&mut self, crlf: bool) {
+        if !std::mem::take(&mut self.line_ending_pending_cr) {
+            return;
+        }
+
+        if let Some(line_ending_inspector) = self.line_ending_inspector.as_ref() {
+            let line_ending = if crlf {
+                LineEnding::CrLf
+            } else {
+                LineEnding::Cr
+            };
+            line_ending_inspector.inspect(line_ending);
+        }
+    }
+
+    /// Decode at most one line from a slice.
+    ///
+    /// Returns the progress made and the number of bytes consumed.
+    pub(crate) fn decode_line_slice(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<(DecodeProgress, usize), SseCodecError> {
+        self.decode_line_slice_with(bytes, None)
+    }
+
+    /// Decode at most one line from a slice.
+    ///
+    /// If `raw_data` is set, the values of data fields are also appended to it as raw bytes,
+    /// and they are not required to be valid utf8.
+    /// Returns the progress made and the number of bytes consumed.
+    pub(crate) fn decode_line_slice_with(
+        &mut self,
+        bytes: &[u8],
+        raw_data: Option<&mut Vec<u8>>,
+    ) -> Result<(DecodeProgress, usize), SseCodecError> {
+        let line_span = self.lines.next_line(bytes);
+
+        if let Some(limit) = self.config.max_line_length {
+            let length = match line_span.as_ref() {
+                Some(line_span) => line_span.end - line_span.start,
+                None => self.lines.partial_line_len(bytes),
+            };
+            if length > limit {
+                return Err(SseCodecError::LineTooLong {
+                    line: self.lines.line + 1,
+                    limit,
+                });
+            }
+        }
+
+        // A partial line counts towards the limit, so an endless line is caught too.
+        self.check_stream_bytes(
+            line_span
+                .as_ref()
+                .map_or(bytes.len(), |line_span| line_span.advance),
+        )?;
+
+        let line_span = match line_span {
+            Some(line_span) => line_span,
+            None => {
+                // Consume the \n of a \r\n split across buffers now, so it is not left in the buffer.
+                let skipped = self.lines.skip_pending_lf(bytes);
+                if !bytes.is_empty() {
+                    self.resolve_pending_cr(skipped > 0);
+                }
+                self.stream_bytes += skipped;
+                if let Some(stats) = self.stats.as_ref() {
+                    stats.add_bytes(skipped);
+                }
+                return Ok((DecodeProgress::Blocked, skipped));
+            }
+        };
+
+        let line_bytes = &bytes[line_span.start..line_span.end];
+        if let Some(raw_data) = raw_data {
+            let is_separator = match &self.config.event_separator {
+                EventSeparator::BlankLine => line_bytes.is_empty(),
+                EventSeparator::Line(separator) => line_bytes == separator.as_bytes(),
+            };
+            if let (false, Some(value)) = (is_separator, self.raw_data_value(line_bytes)) {
+                if let (Some(line_inspector), Ok(line)) = (
+                    self.line_inspector.as_ref(),
+                    std::str::from_utf8(line_bytes),
+                ) {
+                    line_inspector.inspect(line);
+                }
+
+                let value = if self.trims_value("data", value) {
+                    value.strip_prefix(b" ").unwrap_or(value)
+                } else {
+                    value
+                };
+                raw_data.extend_from_slice(value);
+                raw_data.push(b'\n');
+                self.push_data(&String::from_utf8_lossy(value));
+
+                self.consume_line(bytes, &line_span);
+                return Ok((DecodeProgress::Field, line_span.advance));
+            }
+        }
+
+        let line = match self.lines.line_str(line_bytes) {
+            Ok(line) => line,
+            Err(SseCodecError::InvalidUtf8 { .. }) if self.config.utf8_recovery => {
+                #[cfg(feature = "tracing")]
+                {
+                    let line = self.lines.line + 1;
+                    tracing::warn!(line, "skipped line that is not valid utf8");
+                }
+
+                self.consume_line(bytes, &line_span);
+                return Ok((DecodeProgress::SkippedInvalidUtf8, line_span.advance));
+            }
+            Err(error) => return Err(error),
+        };
+
+        if let Some(line_inspector) = self.line_inspector.as_ref() {
+            line_inspector.inspect(line);
+        }
+
+        let is_separator = match &self.config.event_separator {
+            EventSeparator::BlankLine => line.is_empty(),
+            EventSeparator::Line(separator) => line == separator,
+        };
+
+        // Blank lines are not separators when using a custom separator, so ignore them.
+        if line.is_empty() && !is_separator {
+            self.consume_line(bytes, &line_span);
+            return Ok((DecodeProgress::BlankLine, line_span.advance));
+        }
+
+        if is_separator {
+            self.consume_line(bytes, &line_span);
+            self.event_bytes = 0;
+
+            if let Some(data) = self.data.as_mut() {
+                // Trim trailing \n, per-spec.
+                if data.ends_with('\n') {
+                    data.pop();
+                }
+            }
+
+            if let Some(id) = self.id.as_deref() {
+                let last_event_id = self.last_event_id.get_or_insert_with(String::new);
+                last_event_id.clear();
+                last_event_id.push_str(id);
+
+                if self.config.id_history_capacity > 0 {
+                    if self.id_history.len() == self.config.id_history_capacity {
+                        self.id_history.pop_front();
+                    }
+                    self.id_history.push_back(id.to_string());
+                }
+            }
+
+            // If there is no data, don't dispatch, per spec.
+            if self.data.is_none() && self.config.blank_line_policy == BlankLinePolicy::Spec {
+                self.discard_event();
+
+                return Ok((DecodeProgress::BlankLine, line_span.advance));
+            }
+
+            let event = SseEvent {
+                event: self.event.take(),
+                data: self.data.take(),
+                id: self.id.take(),
+                retry: self.retry.take(),
+            };
+            if let Some(stats) = self.stats.as_ref() {
+                stats.add_event();
+            }
+            if let Some(first_event_hook) = self.first_event_hook.take() {
+                first_event_hook.fire();
+            }
+            #[cfg(feature = "tracing")]
+            tracing::trace!(event = ?event.event, id = ?event.id, "dispatched event");
+            return Ok((DecodeProgress::Event(event), line_span.advance));
+        }
+
+        let (field, mut value) = match split_field(line) {
+            Some(field) => field,
+            None => {
+                // TODO: Consider letting user know about comments
+                self.consume_line(bytes, &line_span);
+                return Ok((DecodeProgress::Comment, line_span.advance));
+            }
+        };
+
+        let field = self.resolve_field(field);
+
+        // If it has a starting space, trim that.
+        if self.trims_value(field, value.as_bytes()) {
+            value = trim_value(value);
+        }
+
+        match field {
+            "event" => {
+                // Overwrite old buffer, per spec.
+                let spare = &mut self.spare.event;
+                let event = self.event.get_or_insert_with(|| std::mem::take(spare));
+                event.clear();
+                event.push_str(value);
+            }
+            "data" => self.push_data(value),
+            // Ignore if id has interior NULs, per spec.
+            "id" if !value.contains('\0') => {
+                let spare = &mut self.spare.id;
+                let id = self.id.get_or_insert_with(|| std::mem::take(spare));
+                id.clear();
+                id.push_str(value);
+            }
+            "retry" => {
+                // Ignore if not all ascii digits, per spec.
+                // Also, attempt to parse into usable integer format,
+                // which is implementation-defined by the spec,
+                // as long as it can hold a few seconds in milliseconds.
+                if let Ok(value) = value.parse() {
+                    self.retry = Some(value);
+                    self.last_retry = Some(value);
+                    self.retry_update = Some(value);
+                }
+            }
+            _ => {
+                // Ignore other fields.
+            }
+        }
+
+        self.consume_line(bytes, &line_span);
+
+        Ok((DecodeProgress::Field, line_span.advance))
+    }
+}
+
+impl SseParser {
+    /// Get the name of a field, after applying the field aliases.
+    fn resolve_field<'a>(&'a self, field: &'a str) -> &'a str {
+        self.config
+            .field_aliases
+            .get(field)
+            .map_or(field, String::as_str)
+    }
+
+    /// Get the untrimmed value of a line if it is a data field, after applying the field aliases.
+    ///
+    /// The value is not validated as utf8, but the field name must be.
+    fn raw_data_value<'a>(&self, line: &'a [u8]) -> Option<&'a [u8]> {
+        let (field, value) = match line.iter().position(|b| *b == b':') {
+            Some(0) => return None,
+            Some(index) => (&line[..index], &line[index + 1..]),
+            None => (line, &[][..]),
+        };
+        let field = std::str::from_utf8(field).ok()?;
+
+        (self.resolve_field(field) == "data").then_some(value)
+    }
+
+    /// Check if a single leading space should be trimmed from the value of a field.
+    fn trims_value(&self, field: &str, value: &[u8]) -> bool {
+        let preserve = self.config.preserve_trailing_space && value.iter().all(|b| *b == b' ');
+        !preserve && self.config.field_trim.get(field).copied().unwrap_or(true)
+    }
+
+    /// Append a value to the data buffer, followed by a \n, per spec.
+    fn push_data(&mut self, value: &str) {
+        let spare = &mut self.spare.data;
+        let data = self.data.get_or_insert_with(|| std::mem::take(spare));
+        data.push_str(value);
+        data.push('\n');
+    }
+}
+
+impl Default for SseParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Parse all events, pushing the input in chunks of `chunk_size`.
+    fn parse_chunked(
+        parser: &mut SseParser,
+        data: &[u8],
+        chunk_size: usize,
+    ) -> Result<Vec<SseEvent>, SseCodecError> {
+        let mut events = Vec::new();
+        for chunk in data.chunks(chunk_size) {
+            parser.push(chunk);
+            while let Some(event) = parser.next_event()? {
+                events.push(event);
+            }
+        }
+        parser.finish();
+        while let Some(event) = parser.next_event()? {
+            events.push(event);
+        }
+        Ok(events)
+    }
+
+    #[test]
+    fn field_colon_space() {
+        for newline in ["\n", "\r", "\r\n"] {
+            let test_data = format!("data:test{newline}{newline}data: test{newline}{newline}");
+            let events =
+                parse_chunked(&mut SseParser::new(), test_data.as_bytes(), test_data.len())
+                    .expect("failed to parse");
+            assert!(
+                events
+                    == [
+                        SseEvent::new().with_data("test"),
+                        SseEvent::new().with_data("test")
+                    ],
+                "{newline:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn trailing_nl() {
+        let test_data = b"data\n\ndata\ndata\n\ndata:";
+        let events = parse_chunked(&mut SseParser::new(), test_data, test_data.len())
+            .expect("failed to parse");
+        assert!(
+            events
+                == [
+                    SseEvent::new().with_data(""),
+                    SseEvent::new().with_data("\n")
+                ]
+        );
+    }
+
+    #[test]
+    fn mixed_newlines() {
+        let test_data = b"event: test\r\ndata: a\ndata: b\r\r\n";
+
+        for chunk_size in [1, 2, test_data.len()] {
+            let mut parser = SseParser::new();
+            let events =
+                parse_chunked(&mut parser, test_data, chunk_size).expect("failed to parse");
+            assert!(events == [SseEvent::new().with_event("test").with_data("a\nb")]);
+            assert!(parser.buffered().is_empty());
+        }
+    }
+
+    #[test]
+    fn last_event_id() {
+        let mut parser = SseParser::new();
+        parser.push(b"id: 1\ndata: a\n\nid: 2\n");
+        let event = parser
+            .next_event()
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.id.as_deref() == Some("1"));
+        assert!(parser.next_event().expect("failed to parse").is_none());
+        assert!(parser.last_event_id() == Some("1"));
+    }
+
+    #[test]
+    fn finish_with_codec() {
+        let mut parser = SseParser::with_codec(SseCodec::new().with_eof_error(true));
+        parser.push(b"data: a\n\ndata: partial");
+        parser.finish();
+
+        let event = parser
+            .next_event()
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("a"));
+        assert!(matches!(
+            parser.next_event(),
+            Err(SseCodecError::UnterminatedEvent { .. })
+        ));
+    }
+
+    #[test]
+    fn leading_newline() {
+        // With no buffered fields, a leading blank line is consumed without an event.
+        let mut parser = SseParser::new();
+        parser.push(b"\n");
+        assert!(parser.next_event().expect("failed to parse").is_none());
+        assert!(parser.buffered().is_empty());
+
+        // With buffered fields from a previous push, it completes the event.
+        parser.push(b"data: a\n");
+        assert!(parser.next_event().expect("failed to parse").is_none());
+        parser.push(b"\n");
+        let event = parser
+            .next_event()
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event == SseEvent::new().with_data("a"));
+
+        // After a \r at the end of the previous push, a leading \n is part of that newline, not a blank line.
+        parser.push(b"data: b\r");
+        assert!(parser.next_event().expect("failed to parse").is_none());
+        parser.push(b"\n");
+        assert!(parser.next_event().expect("failed to parse").is_none());
+        parser.push(b"\n");
+        let event = parser
+            .next_event()
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event == SseEvent::new().with_data("b"));
+        assert!(parser.buffered().is_empty());
+    }
+
+    #[test]
+    fn mixed_terminator() {
+        for test_data in [
+            "data: x\n\r\n",
+            "data: x\r\n\n",
+            "data: x\r\n\r\n",
+            "data: x\r\r\n",
+        ] {
+            // Split the input at every position, so each newline may be split across pushes.
+            for split in 1..=test_data.len() {
+                let mut parser = SseParser::new();
+                let events = parse_chunked(&mut parser, test_data.as_bytes(), split)
+                    .expect("failed to parse");
+                assert!(
+                    events == [SseEvent::new().with_data("x")],
+                    "{test_data:?} split at {split}"
+                );
+                assert!(parser.buffered().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn data_colon_space_table() {
+        let table = [
+            ("data:", ""),
+            ("data: ", ""),
+            ("data:x", "x"),
+            ("data: x", "x"),
+            ("data:  x", " x"),
+            ("data", ""),
+        ];
+        for (line, expected) in table {
+            let mut parser = SseParser::new();
+            parser.push(line.as_bytes());
+            parser.push(b"\n");
+
+            // Each data line appends its value and a \n.
+            assert!(parser.next_event().expect("failed to parse").is_none());
+            assert!(
+                parser.data.as_deref() == Some(format!("{expected}\n").as_str()),
+                "{line:?}"
+            );
+
+            // The final \n is trimmed when the event is dispatched.
+            parser.push(b"\n");
+            let event = parser
+                .next_event()
+                .expect("failed to parse")
+                .expect("missing event");
+            assert!(event.data.as_deref() == Some(expected), "{line:?}");
+        }
+    }
+
+    #[test]
+    fn field_order() {
+        let expected_event = SseEvent::new()
+            .with_event("update")
+            .with_data("a\nb")
+            .with_id("1")
+            .with_retry(10);
+        let test_data = [
+            "id: 1\nevent: update\nretry: 10\ndata: a\ndata: b\n\n",
+            "data: a\ndata: b\nevent: update\nid: 1\nretry: 10\n\n",
+            "data: a\nid: 1\nretry: 10\nevent: update\ndata: b\n\n",
+            "retry: 10\ndata: a\nevent: update\n: comment\ndata: b\nid: 1\n\n",
+        ];
+
+        for test_data in test_data {
+            let mut parser = SseParser::new();
+            let events = parse_chunked(&mut parser, test_data.as_bytes(), test_data.len())
+                .expect("failed to parse");
+            assert!(events.len() == 1, "{test_data:?}");
+            assert!(events[0] == expected_event, "{test_data:?}");
+            assert!(parser.last_event_id() == Some("1"));
+        }
+    }
+
+    #[test]
+    fn max_stream_bytes() {
+        let mut parser = SseParser::with_codec(SseCodec::new().with_max_stream_bytes(20));
+        parser.push(b"data: a\n\ndata: b\n\ndata: c\n\n");
+
+        let event = parser
+            .next_event()
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("a"));
+        let event = parser
+            .next_event()
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("b"));
+
+        let error = parser.next_event().expect_err("missing error");
+        assert!(matches!(error, SseCodecError::StreamTooLarge { limit: 20 }));
+
+        // A partial line counts too.
+        let mut parser = SseParser::with_codec(SseCodec::new().with_max_stream_bytes(20));
+        parser.push(b": this comment never ends");
+        let error = parser.next_event().expect_err("missing error");
+        assert!(matches!(error, SseCodecError::StreamTooLarge { limit: 20 }));
+    }
+
+    #[test]
+    fn eof_flush() {
+        let test_data = b"data: a\n\nevent: test\ndata: part1\ndata: part2";
+
+        // Per spec, the partial event is discarded.
+        let events = parse_chunked(&mut SseParser::new(), test_data, 3).expect("failed to parse");
+        assert!(events == [SseEvent::new().with_data("a")]);
+
+        let mut parser = SseParser::with_codec(SseCodec::new().with_eof_flush(true));
+        let events = parse_chunked(&mut parser, test_data, 3).expect("failed to parse");
+        let expected_events = [
+            SseEvent::new().with_data("a"),
+            SseEvent::new().with_event("test").with_data("part1\npart2"),
+        ];
+        assert!(events == expected_events);
+
+        // Newlines at the end of the input, including a \r that may be the start of a \r\n.
+        for test_data in ["data: a\n", "data: a\r", "data: a\r\n"] {
+            let mut parser = SseParser::with_codec(SseCodec::new().with_eof_flush(true));
+            let events = parse_chunked(&mut parser, test_data.as_bytes(), test_data.len())
+                .expect("failed to parse");
+            assert!(events == [SseEvent::new().with_data("a")], "{test_data:?}");
+        }
+    }
+
+    #[test]
+    fn cr_only() {
+        let test_data =
+            b"event: a\rdata: b\r\r: comment\rdata: c\rdata: d\r\r\r\rid: 1\rdata: e\r\r";
+        let expected_events = [
+            SseEvent::new().with_event("a").with_data("b"),
+            SseEvent::new().with_data("c\nd"),
+            SseEvent::new().with_id("1").with_data("e"),
+        ];
+
+        for chunk_size in [1, test_data.len()] {
+            let events = parse_chunked(&mut SseParser::new(), test_data, chunk_size)
+                .expect("failed to parse");
+            assert!(events == expected_events);
+        }
+
+        // An unterminated event at the end of the input is discarded, per spec.
+        let events = parse_chunked(&mut SseParser::new(), b"data: a\r\rdata: b\r", 1)
+            .expect("failed to parse");
+        assert!(events == [SseEvent::new().with_data("a")]);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let mut parser = SseParser::new();
+        parser.push(b"data: a\n\ndata: \xFF\n\n");
+        let event = parser
+            .next_event()
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event.data.as_deref() == Some("a"));
+        assert!(matches!(
+            parser.next_event(),
+            Err(SseCodecError::InvalidUtf8 { line: 3, .. })
+        ));
+
+        let mut parser = SseParser::with_codec(SseCodec::new().with_utf8_recovery(true));
+        let events =
+            parse_chunked(&mut parser, b"data: \xFF\ndata: b\n\n", 1).expect("failed to parse");
+        assert!(events == [SseEvent::new().with_data("b")]);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::LineEnding;
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use crate::SseWriter;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// A proxy that decodes events from a reader, transforms them, and encodes them to a writer.
+///
+/// The transform can modify events, like rewriting ids, or drop them by returning `None`.
+/// Each event is flushed as soon as it is written, so clients receive it promptly.
+#[derive(Debug)]
+pub struct SseProxy<F> {
+    codec: SseCodec,
+    line_ending: LineEnding,
+    transform: F,
+}
+
+impl<F> SseProxy<F>
+where
+    F: FnMut(SseEvent) -> Option<SseEvent>,
+{
+    /// Make a new proxy from a transform.
+    pub fn new(transform: F) -> Self {
+        Self {
+            codec: SseCodec::new(),
+            line_ending: LineEnding::Lf,
+            transform,
+        }
+    }
+
+    /// Set the codec used to decode events.
+    pub fn with_codec(mut self, codec: SseCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Set the newline written after each line.
+    ///
+    /// See [`SseWriter::with_line_ending`].
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Forward events from the reader to the writer, until the reader ends.
+    ///
+    /// Returns the number of events written.
+    pub async fn run<R, W>(&mut self, mut reader: R, writer: W) -> Result<u64, SseCodecError>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut writer = SseWriter::new(writer).with_line_ending(self.line_ending);
+        let mut buffer = BytesMut::new();
+        let mut written = 0;
+
+        loop {
+            let eof = reader.read_buf(&mut buffer).await? == 0;
+
+            loop {
+                let event = if eof {
+                    self.codec.decode_eof(&mut buffer)?
+                } else {
+                    self.codec.decode(&mut buffer)?
+                };
+                let event = match event {
+                    Some(event) => event,
+                    None => break,
+                };
+
+                if let Some(event) = (self.transform)(event) {
+                    writer.send_event_flush(&event).await?;
+                    written += 1;
+                }
+            }
+
+            if eof {
+                return Ok(written);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn proxy() {
+        let test_data = "id: a\ndata: 1\n\n: comment\nevent: secret\ndata: 2\n\nid: b\nevent: x\ndata: 3\ndata: 4\r\n\r\n";
+
+        let mut sequence = 0;
+        let mut proxy = SseProxy::new(|event: SseEvent| {
+            if event.event.as_deref() == Some("secret") {
+                return None;
+            }
+            sequence += 1;
+            Some(event.with_id(sequence.to_string()))
+        })
+        .with_line_ending(LineEnding::CrLf);
+
+        let mut output = Vec::new();
+        let written = proxy
+            .run(test_data.as_bytes(), &mut output)
+            .await
+            .expect("failed to proxy");
+        assert!(written == 2);
+        assert!(
+            output == b"data: 1\r\nid: 1\r\n\r\nevent: x\r\ndata: 3\r\ndata: 4\r\nid: 2\r\n\r\n"
+        );
+
+        let mut buffer = BytesMut::from(&output[..]);
+        let mut codec = SseCodec::new();
+        let mut events = Vec::new();
+        while let Some(event) = codec.decode(&mut buffer).expect("failed to parse") {
+            events.push(event);
+        }
+        assert!(
+            events
+                == [
+                    SseEvent::new().with_data("1").with_id("1"),
+                    SseEvent::new()
+                        .with_event("x")
+                        .with_data("3\n4")
+                        .with_id("2")
+                ]
+        );
+    }
+}
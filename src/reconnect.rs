@@ -0,0 +1,419 @@
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use futures_util::stream::Stream;
+use futures_util::StreamExt;
+use std::future::Future;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio_util::codec::FramedRead;
+
+/// A policy deciding which errors trigger a reconnect
+pub struct ReconnectPolicy {
+    should_reconnect: Box<dyn Fn(&SseCodecError) -> bool + Send + Sync>,
+}
+
+impl ReconnectPolicy {
+    /// Make a new policy from a predicate.
+    ///
+    /// The predicate returns true if the error should trigger a reconnect.
+    pub fn new<F>(should_reconnect: F) -> Self
+    where
+        F: Fn(&SseCodecError) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            should_reconnect: Box::new(should_reconnect),
+        }
+    }
+
+    /// Check if an error should trigger a reconnect.
+    pub fn should_reconnect(&self, error: &SseCodecError) -> bool {
+        (self.should_reconnect)(error)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    /// Reconnect on IO errors and read timeouts, and fail on all other errors.
+    ///
+    /// Other errors, like invalid utf8, usually mean that the server is not an SSE endpoint.
+    fn default() -> Self {
+        Self::new(|error| {
+            matches!(
+                error,
+                SseCodecError::Io(_) | SseCodecError::ReadTimeout { .. }
+            )
+        })
+    }
+}
+
+impl std::fmt::Debug for ReconnectPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ReconnectPolicy").finish_non_exhaustive()
+    }
+}
+
+/// A builder for a stream of sse events that reconnects when the connection is lost.
+///
+/// The connect function is called with the last event id,
+/// which should be sent in the `Last-Event-ID` header.
+/// The end of the stream will trigger a reconnect after the reconnection time.
+/// Errors, from either the connect function or the stream, trigger a reconnect if allowed by the [`ReconnectPolicy`].
+/// Other errors are yielded and end the stream.
+#[derive(Debug)]
+pub struct SseReconnect<F> {
+    /// The connect function
+    connect: F,
+
+    /// The codec
+    codec: SseCodec,
+
+    /// The time to wait before reconnecting, if set.
+    reconnection_time: Option<Duration>,
+
+    /// The policy for which errors trigger a reconnect.
+    policy: ReconnectPolicy,
+
+    /// The maximum number of consecutive failed reconnect attempts.
+    max_reconnects: Option<usize>,
+}
+
+impl<F, Fut, R> SseReconnect<F>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<R, SseCodecError>>,
+    R: AsyncRead + Unpin,
+{
+    /// Make a new reconnecting stream builder from a connect function.
+    pub fn new(connect: F) -> Self {
+        Self {
+            connect,
+            codec: SseCodec::new(),
+            reconnection_time: None,
+            policy: ReconnectPolicy::default(),
+            max_reconnects: None,
+        }
+    }
+
+    /// Set the maximum number of consecutive failed reconnect attempts.
+    ///
+    /// Once a connection fails and has been retried this many times without success,
+    /// the stream yields the last error and ends.
+    /// A reconnect that succeeds but ends before yielding an event also counts as a failure,
+    /// and the stream ends without an error if that failure was not caused by one.
+    /// Receiving an event resets the count.
+    /// By default, there is no limit.
+    pub fn with_max_reconnects(mut self, max_reconnects: usize) -> Self {
+        self.max_reconnects = Some(max_reconnects);
+        self
+    }
+
+    /// Set the policy for which errors trigger a reconnect.
+    ///
+    /// By default, only IO errors and read timeouts trigger a reconnect.
+    pub fn with_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the codec used for each connection.
+    ///
+    /// Each connection uses a continuation of this codec, see [`SseCodec::new_with_continuation`].
+    /// The reconnection time comes from [`SseCodec::next_reconnect_delay`].
+    pub fn with_codec(mut self, codec: SseCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Set the initial reconnection time.
+    ///
+    /// This is replaced by the retry field of events, if the server sends one.
+    /// This overrides the default reconnect delay of the codec, see [`SseCodec::with_default_reconnect_delay`].
+    /// By default, this is 3 seconds.
+    pub fn with_reconnection_time(mut self, reconnection_time: Duration) -> Self {
+        self.reconnection_time = Some(reconnection_time);
+        self
+    }
+
+    /// Make the stream.
+    pub fn into_stream(self) -> impl Stream<Item = Result<SseEvent, SseCodecError>> {
+        let codec = match self.reconnection_time {
+            Some(reconnection_time) => self.codec.with_default_reconnect_delay(reconnection_time),
+            None => self.codec,
+        };
+        let state = ReconnectState {
+            connect: self.connect,
+            codec,
+            reader: None,
+            policy: self.policy,
+            max_reconnects: self.max_reconnects,
+            failed_reconnects: 0,
+            received_event: false,
+            reconnecting: false,
+            done: false,
+        };
+
+        futures_util::stream::unfold(state, |mut state| async move {
+            let result = state.next().await?;
+            Some((result, state))
+        })
+    }
+}
+
+/// The state of a reconnecting stream
+struct ReconnectState<F, R> {
+    connect: F,
+    codec: SseCodec,
+    reader: Option<FramedRead<R, SseCodec>>,
+    policy: ReconnectPolicy,
+    max_reconnects: Option<usize>,
+    failed_reconnects: usize,
+    received_event: bool,
+    reconnecting: bool,
+    done: bool,
+}
+
+impl<F, Fut, R> ReconnectState<F, R>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<R, SseCodecError>>,
+    R: AsyncRead + Unpin,
+{
+    async fn next(&mut self) -> Option<Result<SseEvent, SseCodecError>> {
+        loop {
+            if self.done {
+                return None;
+            }
+
+            let reader = match self.reader.as_mut() {
+                Some(reader) => reader,
+                None => {
+                    if self.reconnecting {
+                        tokio::time::sleep(self.codec.next_reconnect_delay()).await;
+                    }
+
+                    let last_event_id = self.codec.last_event_id().map(String::from);
+                    match (self.connect)(last_event_id).await {
+                        Ok(reader) => {
+                            let codec = self.codec.new_with_continuation();
+                            self.reader = Some(FramedRead::new(reader, codec));
+                            self.received_event = false;
+                        }
+                        Err(error) if self.policy.should_reconnect(&error) => {
+                            if self.reconnecting {
+                                self.failed_reconnects += 1;
+                            }
+
+                            if self
+                                .max_reconnects
+                                .is_some_and(|max| self.failed_reconnects >= max)
+                            {
+                                self.done = true;
+                                return Some(Err(error));
+                            }
+
+                            self.reconnecting = true;
+                        }
+                        Err(error) => {
+                            self.done = true;
+                            return Some(Err(error));
+                        }
+                    }
+
+                    continue;
+                }
+            };
+
+            match reader.next().await {
+                Some(Ok(event)) => {
+                    self.failed_reconnects = 0;
+                    self.received_event = true;
+                    return Some(Ok(event));
+                }
+                Some(Err(error)) if !self.policy.should_reconnect(&error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+                result => {
+                    // Keep the last event id and retry value for the next connection.
+                    self.codec = reader.decoder().new_with_continuation();
+                    self.reader = None;
+
+                    // A reconnect that ends before any event did not really succeed.
+                    if self.reconnecting && !self.received_event {
+                        self.failed_reconnects += 1;
+                        if self
+                            .max_reconnects
+                            .is_some_and(|max| self.failed_reconnects >= max)
+                        {
+                            self.done = true;
+                            return result;
+                        }
+                    }
+
+                    self.reconnecting = true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn reconnects_with_last_event_id() {
+        let mut last_event_ids = Vec::new();
+        let stream = SseReconnect::new(|last_event_id: Option<String>| {
+            let response: &[u8] = match last_event_id.as_deref() {
+                None => b"id: 1\nretry: 1\ndata: a\n\ndata: partial\n",
+                Some(_) => b"data: b\n\n",
+            };
+            last_event_ids.push(last_event_id);
+            async move { Ok(response) }
+        })
+        .into_stream();
+
+        let events: Vec<_> = stream.take(3).collect().await;
+        let data: Vec<_> = events
+            .into_iter()
+            .map(|event| event.expect("failed to parse").data)
+            .collect();
+        assert!(data == [Some("a".into()), Some("b".into()), Some("b".into())]);
+        assert!(last_event_ids == [None, Some("1".into()), Some("1".into())]);
+    }
+
+    /// Make a connect function that returns each response in order, and then IO errors.
+    fn responses(
+        responses: Vec<Result<&'static [u8], SseCodecError>>,
+    ) -> impl FnMut(Option<String>) -> std::future::Ready<Result<&'static [u8], SseCodecError>>
+    {
+        let mut responses = responses.into_iter();
+        move |_| {
+            std::future::ready(responses.next().unwrap_or_else(|| {
+                Err(SseCodecError::Io(
+                    std::io::ErrorKind::ConnectionRefused.into(),
+                ))
+            }))
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn default_policy() {
+        let connect = responses(vec![
+            Err(SseCodecError::Io(
+                std::io::ErrorKind::ConnectionReset.into(),
+            )),
+            Ok(b"data: a\n\n"),
+            Ok(b"data: \xFF\n\n"),
+            Ok(b"data: b\n\n"),
+        ]);
+        let events: Vec<_> = SseReconnect::new(connect).into_stream().collect().await;
+
+        assert!(events.len() == 2);
+        assert!(events[0].as_ref().expect("failed to parse").data.as_deref() == Some("a"));
+        assert!(matches!(events[1], Err(SseCodecError::InvalidUtf8 { .. })));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn custom_policy() {
+        let connect = responses(vec![
+            Ok(b"data: a\n\n"),
+            Ok(b"data: \xFF\n\n"),
+            Ok(b"data: b\n\n"),
+            Err(SseCodecError::InvalidStatus(500)),
+        ]);
+        let policy = ReconnectPolicy::new(|error| {
+            matches!(
+                error,
+                SseCodecError::Io(_) | SseCodecError::InvalidUtf8 { .. }
+            )
+        });
+        let events: Vec<_> = SseReconnect::new(connect)
+            .with_policy(policy)
+            .into_stream()
+            .collect()
+            .await;
+
+        assert!(events.len() == 3);
+        assert!(events[0].as_ref().expect("failed to parse").data.as_deref() == Some("a"));
+        assert!(events[1].as_ref().expect("failed to parse").data.as_deref() == Some("b"));
+        assert!(matches!(events[2], Err(SseCodecError::InvalidStatus(500))));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn max_reconnects() {
+        let mut attempts = 0;
+        let events: Vec<_> = SseReconnect::new(|_| {
+            attempts += 1;
+            let error = std::io::Error::other(format!("attempt {attempts}"));
+            std::future::ready(Err::<&[u8], _>(SseCodecError::Io(error)))
+        })
+        .with_max_reconnects(3)
+        .into_stream()
+        .collect()
+        .await;
+
+        // The first attempt, then 3 reconnects.
+        assert!(attempts == 4);
+        assert!(events.len() == 1);
+        assert!(matches!(
+            &events[0],
+            Err(SseCodecError::Io(error)) if error.to_string() == "attempt 4"
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn max_reconnects_empty_connections() {
+        let mut attempts = 0;
+        let events: Vec<_> = SseReconnect::new(|_| {
+            attempts += 1;
+            let response: &[u8] = match attempts {
+                1 => b"data: a\n\n",
+                3 => b"data: b\n\n",
+                _ => b"",
+            };
+            std::future::ready(Ok(response))
+        })
+        .with_max_reconnects(3)
+        .into_stream()
+        .collect()
+        .await;
+
+        // The event on the third attempt resets the count,
+        // and the stream ends after 3 more connections that close without an event.
+        assert!(attempts == 6);
+        let data: Vec<_> = events
+            .into_iter()
+            .map(|event| event.expect("failed to parse").data)
+            .collect();
+        assert!(data == [Some("a".into()), Some("b".into())]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zero_retry_delay() {
+        let test_data = [
+            (SseCodec::new(), Duration::from_millis(100)),
+            (
+                SseCodec::new().with_min_reconnect_delay(Duration::from_millis(20)),
+                Duration::from_millis(20),
+            ),
+        ];
+        for (codec, expected) in test_data {
+            let start = tokio::time::Instant::now();
+            let mut connect_times = Vec::new();
+            let events: Vec<_> = SseReconnect::new(|_| {
+                connect_times.push(start.elapsed());
+                std::future::ready(Ok::<&[u8], SseCodecError>(b"retry: 0\ndata: a\n\n"))
+            })
+            .with_codec(codec)
+            .into_stream()
+            .take(3)
+            .collect()
+            .await;
+
+            assert!(events.len() == 3);
+            assert!(connect_times == [Duration::ZERO, expected, expected * 2]);
+        }
+    }
+}
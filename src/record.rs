@@ -0,0 +1,301 @@
+use std::future::Future;
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio::io::ReadBuf;
+use tokio::time::Instant;
+use tokio::time::Sleep;
+use tokio_util::bytes::Bytes;
+
+/// A recorded sse session, as raw bytes with timing
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SseRecording {
+    /// Each read, with the time since the start of the recording.
+    chunks: Vec<(Duration, Bytes)>,
+}
+
+impl SseRecording {
+    /// Make a new, empty recording.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chunk of bytes, received at the given time since the start of the recording.
+    pub fn push(&mut self, elapsed: Duration, bytes: impl Into<Bytes>) {
+        self.chunks.push((elapsed, bytes.into()));
+    }
+
+    /// Get the chunks of this recording.
+    pub fn chunks(&self) -> &[(Duration, Bytes)] {
+        &self.chunks
+    }
+
+    /// Serialize this recording.
+    ///
+    /// Each chunk is stored as the elapsed time in microseconds as a little-endian u64,
+    /// the length of the chunk as a little-endian u64, and then the bytes of the chunk.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for (elapsed, chunk) in self.chunks.iter() {
+            let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+            bytes.extend_from_slice(&micros.to_le_bytes());
+            bytes.extend_from_slice(&(chunk.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(chunk);
+        }
+        bytes
+    }
+
+    /// Parse a recording serialized with [`SseRecording::to_bytes`].
+    pub fn from_bytes(mut bytes: &[u8]) -> std::io::Result<Self> {
+        /// Take a little-endian u64 from the start of the bytes.
+        fn take_u64(bytes: &mut &[u8]) -> std::io::Result<u64> {
+            let (value, rest) = bytes
+                .split_first_chunk::<8>()
+                .ok_or(ErrorKind::UnexpectedEof)?;
+            *bytes = rest;
+            Ok(u64::from_le_bytes(*value))
+        }
+
+        let mut recording = Self::new();
+        while !bytes.is_empty() {
+            let micros = take_u64(&mut bytes)?;
+            let len = usize::try_from(take_u64(&mut bytes)?)
+                .map_err(|error| std::io::Error::new(ErrorKind::InvalidData, error))?;
+            if bytes.len() < len {
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+            let (chunk, rest) = bytes.split_at(len);
+            bytes = rest;
+
+            recording.push(Duration::from_micros(micros), Bytes::copy_from_slice(chunk));
+        }
+
+        Ok(recording)
+    }
+}
+
+/// A reader that records the bytes read from another reader, with timing.
+///
+/// Time is measured from when the recorder is made.
+#[derive(Debug)]
+pub struct SseRecorder<R> {
+    reader: R,
+    start: Instant,
+    recording: SseRecording,
+}
+
+impl<R> SseRecorder<R> {
+    /// Wrap a reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            start: Instant::now(),
+            recording: SseRecording::new(),
+        }
+    }
+
+    /// Get the recording so far.
+    pub fn recording(&self) -> &SseRecording {
+        &self.recording
+    }
+
+    /// Get the inner reader and the recording.
+    pub fn into_parts(self) -> (R, SseRecording) {
+        (self.reader, self.recording)
+    }
+}
+
+impl<R> AsyncRead for SseRecorder<R>
+where
+    R: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut self.reader).poll_read(cx, buf);
+
+        if let Poll::Ready(Ok(())) = poll {
+            let chunk = &buf.filled()[filled..];
+            if !chunk.is_empty() {
+                let elapsed = self.start.elapsed();
+                self.recording.push(elapsed, Bytes::copy_from_slice(chunk));
+            }
+        }
+
+        poll
+    }
+}
+
+/// A reader that replays a recording, with the recorded timing.
+///
+/// Time is measured from the first read.
+/// Chunks are delayed with tokio timers, so replays are deterministic when the tokio clock is paused.
+#[derive(Debug)]
+pub struct SseReplayer {
+    recording: SseRecording,
+
+    /// The index of the next chunk.
+    index: usize,
+
+    /// The number of bytes of the next chunk that were already read.
+    offset: usize,
+
+    /// The time of the first read.
+    start: Option<Instant>,
+
+    /// The timer for the next chunk.
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl SseReplayer {
+    /// Make a new replayer.
+    pub fn new(recording: SseRecording) -> Self {
+        Self {
+            recording,
+            index: 0,
+            offset: 0,
+            start: None,
+            sleep: None,
+        }
+    }
+}
+
+impl AsyncRead for SseReplayer {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+
+        // An empty read means the end of the stream, so empty chunks are skipped.
+        while this
+            .recording
+            .chunks
+            .get(this.index)
+            .is_some_and(|(_, chunk)| chunk.is_empty())
+        {
+            this.index += 1;
+        }
+
+        let (elapsed, chunk) = match this.recording.chunks.get(this.index) {
+            Some(chunk) => chunk,
+            None => return Poll::Ready(Ok(())),
+        };
+
+        let start = *this.start.get_or_insert_with(Instant::now);
+        let deadline = start + *elapsed;
+        if Instant::now() < deadline {
+            let sleep = this
+                .sleep
+                .get_or_insert_with(|| Box::pin(tokio::time::sleep_until(deadline)));
+            if sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+        this.sleep = None;
+
+        let rest = &chunk[this.offset..];
+        let n = rest.len().min(buf.remaining());
+        buf.put_slice(&rest[..n]);
+
+        this.offset += n;
+        if this.offset == chunk.len() {
+            this.index += 1;
+            this.offset = 0;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SseCodec;
+    use tokio::io::AsyncWriteExt;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    /// Read all events from a reader, with the time each one arrived.
+    async fn timed_events<R>(reader: R) -> Vec<(Duration, Option<String>)>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let start = Instant::now();
+        let mut reader = FramedRead::new(reader, SseCodec::new());
+        let mut events = Vec::new();
+        while let Some(event) = reader.next().await {
+            let event = event.expect("failed to parse");
+            events.push((start.elapsed(), event.data));
+        }
+        events
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn round_trip() {
+        let (mut writer, reader) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            for (delay, chunk) in [
+                (0, "data: a\n\ndata: "),
+                (100, "b\n\n"),
+                (250, "data: c\n\n"),
+            ] {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                writer
+                    .write_all(chunk.as_bytes())
+                    .await
+                    .expect("failed to write");
+            }
+        });
+
+        let mut recorder = SseRecorder::new(reader);
+        let recorded_events = timed_events(&mut recorder).await;
+        let (_, recording) = recorder.into_parts();
+
+        let expected_events = [
+            (Duration::ZERO, Some("a".into())),
+            (Duration::from_millis(100), Some("b".into())),
+            (Duration::from_millis(350), Some("c".into())),
+        ];
+        assert!(recorded_events == expected_events);
+
+        let recording =
+            SseRecording::from_bytes(&recording.to_bytes()).expect("failed to parse recording");
+        assert!(recording.chunks().len() == 3);
+
+        let replayed_events = timed_events(SseReplayer::new(recording)).await;
+        assert!(replayed_events == expected_events);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn replay_empty_chunk() {
+        let mut recording = SseRecording::new();
+        recording.push(Duration::ZERO, &b"data: a\n\n"[..]);
+        recording.push(Duration::from_millis(10), Bytes::new());
+        recording.push(Duration::from_millis(20), &b"data: b\n\n"[..]);
+
+        let replayed_events = timed_events(SseReplayer::new(recording)).await;
+        let expected_events = [
+            (Duration::ZERO, Some("a".into())),
+            (Duration::from_millis(20), Some("b".into())),
+        ];
+        assert!(replayed_events == expected_events);
+    }
+
+    #[test]
+    fn truncated_recording() {
+        let mut recording = SseRecording::new();
+        recording.push(Duration::from_millis(1), &b"data: a\n\n"[..]);
+        let bytes = recording.to_bytes();
+
+        let error = SseRecording::from_bytes(&bytes[..bytes.len() - 1]).expect_err("missing error");
+        assert!(error.kind() == ErrorKind::UnexpectedEof);
+    }
+}
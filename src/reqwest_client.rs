@@ -0,0 +1,147 @@
+use crate::SseCodecError;
+use crate::SseEvent;
+use crate::SseReconnect;
+use futures_util::stream::Stream;
+use futures_util::TryStreamExt;
+use reqwest::header::HeaderValue;
+use reqwest::header::ACCEPT;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use tokio_util::io::StreamReader;
+
+/// The header used to resume a stream.
+const LAST_EVENT_ID: &str = "Last-Event-ID";
+
+/// Make a stream of sse events from a reqwest request.
+///
+/// The request is sent with an `Accept: text/event-stream` header.
+/// The response must have a 200 status and a `text/event-stream` content type.
+/// When the connection is lost, the request is resent with the `Last-Event-ID` header,
+/// after waiting for the reconnection time sent by the server.
+/// See [`SseReconnect`] for details.
+///
+/// The request must be cloneable, so it cannot have a streaming body.
+pub fn reqwest_sse(
+    client: reqwest::Client,
+    request: reqwest::Request,
+) -> impl Stream<Item = Result<SseEvent, SseCodecError>> {
+    SseReconnect::new(move |last_event_id: Option<String>| {
+        let client = client.clone();
+        let request = request.try_clone();
+
+        async move {
+            let mut request = request.ok_or(SseCodecError::UncloneableRequest)?;
+
+            let headers = request.headers_mut();
+            headers.insert(ACCEPT, HeaderValue::from_static("text/event-stream"));
+            if let Some(value) =
+                last_event_id.and_then(|id| HeaderValue::from_bytes(id.as_bytes()).ok())
+            {
+                headers.insert(LAST_EVENT_ID, value);
+            }
+
+            let response = client
+                .execute(request)
+                .await
+                .map_err(std::io::Error::other)?;
+
+            let status = response.status();
+            if status != StatusCode::OK {
+                return Err(SseCodecError::InvalidStatus(status.as_u16()));
+            }
+
+            let content_type = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok());
+            let is_event_stream = content_type
+                .and_then(|value| value.split(';').next())
+                .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("text/event-stream"));
+            if !is_event_stream {
+                return Err(SseCodecError::InvalidContentType(
+                    content_type.map(String::from),
+                ));
+            }
+
+            let stream = response.bytes_stream().map_err(std::io::Error::other);
+            Ok(StreamReader::new(stream))
+        }
+    })
+    .into_stream()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures_util::StreamExt;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    /// Accept a connection, returning the request head and responding with the given body.
+    async fn respond(listener: &TcpListener, content_type: &str, body: &str) -> String {
+        let (mut socket, _) = listener.accept().await.expect("failed to accept");
+
+        let mut request = Vec::new();
+        while !request.ends_with(b"\r\n\r\n") {
+            let mut buffer = [0; 1024];
+            let n = socket.read(&mut buffer).await.expect("failed to read");
+            assert!(n != 0);
+            request.extend_from_slice(&buffer[..n]);
+        }
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        socket
+            .write_all(response.as_bytes())
+            .await
+            .expect("failed to write");
+        socket.shutdown().await.expect("failed to shutdown");
+
+        String::from_utf8(request)
+            .expect("request was not utf8")
+            .to_ascii_lowercase()
+    }
+
+    #[tokio::test]
+    async fn reqwest_sse_reconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind");
+        let url = format!("http://{}/", listener.local_addr().expect("missing addr"));
+
+        let server = tokio::spawn(async move {
+            let request_1 = respond(
+                &listener,
+                "text/event-stream",
+                "id: 1\nretry: 10\ndata: a\n\n",
+            )
+            .await;
+            let request_2 =
+                respond(&listener, "text/event-stream; charset=utf-8", "data: b\n\n").await;
+            let request_3 = respond(&listener, "text/html", "").await;
+
+            (request_1, request_2, request_3)
+        });
+
+        let client = reqwest::Client::new();
+        let request = client.get(url).build().expect("failed to build request");
+        let events: Vec<_> = reqwest_sse(client, request).collect().await;
+
+        assert!(events.len() == 3);
+        assert!(events[0].as_ref().expect("failed to parse").data.as_deref() == Some("a"));
+        assert!(events[1].as_ref().expect("failed to parse").data.as_deref() == Some("b"));
+        assert!(matches!(
+            events[2],
+            Err(SseCodecError::InvalidContentType(Some(ref content_type))) if content_type == "text/html"
+        ));
+
+        let (request_1, request_2, request_3) = server.await.expect("server failed");
+        assert!(request_1.contains("accept: text/event-stream\r\n"));
+        assert!(!request_1.contains("last-event-id"));
+        assert!(request_2.contains("last-event-id: 1\r\n"));
+        assert!(request_3.contains("last-event-id: 1\r\n"));
+    }
+}
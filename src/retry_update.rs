@@ -0,0 +1,84 @@
+use crate::DecodeProgress;
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseStreamItem;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// An sse codec that yields [`SseStreamItem::RetryUpdate`] as soon as a retry field is parsed.
+///
+/// The retry value is still included in the next dispatched event.
+/// Make one with [`SseCodec::with_retry_updates`].
+#[derive(Debug, Default)]
+pub struct RetryUpdateSseCodec {
+    codec: SseCodec,
+}
+
+impl SseCodec {
+    /// Wrap this codec, so that it yields [`SseStreamItem::RetryUpdate`] for each retry field.
+    ///
+    /// This allows reacting to a new reconnection time immediately,
+    /// even if the retry field is not followed by an event.
+    pub fn with_retry_updates(mut self) -> RetryUpdateSseCodec {
+        self.parser.retry_update = None;
+        RetryUpdateSseCodec { codec: self }
+    }
+}
+
+impl Decoder for RetryUpdateSseCodec {
+    type Item = SseStreamItem;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            match self.codec.decode_line(bytes)? {
+                DecodeProgress::Field => {
+                    if let Some(retry) = self.codec.parser.retry_update.take() {
+                        return Ok(Some(SseStreamItem::RetryUpdate(retry)));
+                    }
+                }
+                DecodeProgress::Comment
+                | DecodeProgress::SkippedInvalidUtf8
+                | DecodeProgress::BlankLine => {}
+                DecodeProgress::Event(event) => return Ok(Some(SseStreamItem::Event(event))),
+                DecodeProgress::Blocked => return Ok(None),
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(bytes)? {
+            Some(item) => Ok(Some(item)),
+            None => {
+                let event = self.codec.decode_eof(bytes)?;
+                Ok(event.map(SseStreamItem::Event))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SseEvent;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    #[tokio::test]
+    async fn retry_updates() {
+        let test_data = "retry: 5000\n\nretry: x\ndata: a\n\nretry: 10\ndata: b\n\n";
+        let reader = FramedRead::new(test_data.as_bytes(), SseCodec::new().with_retry_updates());
+        let items: Vec<_> = reader
+            .map(|item| item.expect("failed to parse"))
+            .collect()
+            .await;
+
+        let expected_items = [
+            SseStreamItem::RetryUpdate(5000),
+            SseStreamItem::Event(SseEvent::new().with_data("a")),
+            SseStreamItem::RetryUpdate(10),
+            SseStreamItem::Event(SseEvent::new().with_data("b").with_retry(10)),
+        ];
+        assert!(items == expected_items);
+    }
+}
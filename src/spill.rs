@@ -0,0 +1,186 @@
+use crate::DecodeProgress;
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// A counter for making unique spill file names.
+static SPILL_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// An item yielded by [`crate::stream::spill_events`]
+#[derive(Debug)]
+pub enum SpillItem {
+    /// An event with its data in memory.
+    Event(SseEvent),
+
+    /// An event with data that was too large to keep in memory.
+    Spilled {
+        /// The event, without its data field.
+        event: SseEvent,
+
+        /// The file holding the data, opened for reading and rewound to the start.
+        file: File,
+
+        /// The path of the file.
+        ///
+        /// The file is not deleted by the stream.
+        path: PathBuf,
+    },
+}
+
+/// A frame yielded by a [`SpillingSseCodec`]
+#[derive(Debug, PartialEq)]
+pub enum SpillFrame {
+    /// An event with its data in memory.
+    Event(SseEvent),
+
+    /// A chunk of data of the current event, which is too large to keep in memory.
+    ///
+    /// It should be appended to the spill file of the event.
+    Data(String),
+
+    /// The end of an event with spilled data.
+    ///
+    /// The data field holds the rest of the data, which should be appended to the spill file.
+    End(SseEvent),
+}
+
+/// An sse codec that hands off large data fields to be written to files, instead of keeping them in memory.
+///
+/// Make one with [`SseCodec::with_spill_threshold`].
+/// The codec does no IO itself, use [`crate::stream::spill_events`] to write the spill files.
+#[derive(Debug)]
+pub struct SpillingSseCodec {
+    codec: SseCodec,
+
+    /// The data size that triggers spilling, in bytes.
+    threshold: usize,
+
+    /// The directory for spill files.
+    dir: PathBuf,
+
+    /// Whether the data of the current event was spilled.
+    spilled: bool,
+}
+
+impl SseCodec {
+    /// Wrap this codec, so that data fields larger than `threshold` bytes are written to a file in `dir`.
+    ///
+    /// The files are written by [`crate::stream::spill_events`],
+    /// which yields spilled events as [`SpillItem::Spilled`], and other events as [`SpillItem::Event`].
+    pub fn with_spill_threshold(
+        self,
+        threshold: usize,
+        dir: impl Into<PathBuf>,
+    ) -> SpillingSseCodec {
+        SpillingSseCodec {
+            codec: self,
+            threshold,
+            dir: dir.into(),
+            spilled: false,
+        }
+    }
+}
+
+impl SpillingSseCodec {
+    /// Make a new unique path for a spill file.
+    pub(crate) fn new_spill_path(&self) -> PathBuf {
+        let n = SPILL_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        self.dir
+            .join(format!("sse-spill-{}-{n}", std::process::id()))
+    }
+
+    /// Take the buffered data, if it is over the threshold.
+    fn take_spill_data(&mut self) -> Option<String> {
+        let data = match self.codec.parser.data.as_mut() {
+            Some(data) if data.len() > self.threshold => data,
+            _ => return None,
+        };
+
+        // Keep the trailing newline in memory, so the codec still trims it when the event is dispatched.
+        let mut chunk = std::mem::replace(data, String::from("\n"));
+        chunk.pop();
+        self.spilled = true;
+
+        Some(chunk)
+    }
+
+    /// Make a frame from a dispatched event.
+    fn finish_event(&mut self, event: SseEvent) -> SpillFrame {
+        if std::mem::take(&mut self.spilled) {
+            SpillFrame::End(event)
+        } else {
+            SpillFrame::Event(event)
+        }
+    }
+}
+
+impl Decoder for SpillingSseCodec {
+    type Item = SpillFrame;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            match self.codec.decode_line(bytes)? {
+                DecodeProgress::Field => {
+                    if let Some(chunk) = self.take_spill_data() {
+                        return Ok(Some(SpillFrame::Data(chunk)));
+                    }
+                }
+                DecodeProgress::Comment
+                | DecodeProgress::SkippedInvalidUtf8
+                | DecodeProgress::BlankLine => {}
+                DecodeProgress::Event(event) => return Ok(Some(self.finish_event(event))),
+                DecodeProgress::Blocked => return Ok(None),
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(frame) = self.decode(bytes)? {
+            return Ok(Some(frame));
+        }
+
+        let event = self.codec.decode_eof(bytes)?;
+        match event {
+            Some(event) => Ok(Some(self.finish_event(event))),
+            None => {
+                // The partial event was discarded.
+                self.spilled = false;
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    #[tokio::test]
+    async fn spill() {
+        let test_data = "data: small\n\nevent: big\ndata: 0123456789\ndata: abcdefghij\n\ndata: 0123456789\ndata: 0123456789\ndata: x\n\ndata: partial 0123456789\n";
+        let codec = SseCodec::new().with_spill_threshold(16, std::env::temp_dir());
+        let frames: Vec<_> = FramedRead::new(test_data.as_bytes(), codec)
+            .map(|frame| frame.expect("failed to parse"))
+            .collect()
+            .await;
+
+        let expected = [
+            SpillFrame::Event(SseEvent::new().with_data("small")),
+            SpillFrame::Data("0123456789\nabcdefghij".into()),
+            SpillFrame::End(SseEvent::new().with_event("big").with_data("")),
+            SpillFrame::Data("0123456789\n0123456789".into()),
+            SpillFrame::End(SseEvent::new().with_data("\nx")),
+            SpillFrame::Data("partial 0123456789".into()),
+        ];
+        assert!(frames == expected);
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+/// The counters shared by a codec and its stats handles
+#[derive(Debug, Default)]
+pub(crate) struct SseStats {
+    /// The number of bytes consumed.
+    bytes: AtomicU64,
+
+    /// The number of events dispatched.
+    events: AtomicU64,
+}
+
+impl SseStats {
+    /// Record consumed bytes.
+    pub(crate) fn add_bytes(&self, bytes: usize) {
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Record a dispatched event.
+    pub(crate) fn add_event(&self) {
+        self.events.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// A handle for reading the stats of a codec from another task
+///
+/// Make one with [`SseCodec::stats_handle`](crate::SseCodec::stats_handle).
+/// Handles are cheap to clone, and reading them does not lock the codec.
+#[derive(Debug, Clone)]
+pub struct SseStatsHandle {
+    stats: Arc<SseStats>,
+}
+
+impl SseStatsHandle {
+    /// Make a handle for the given stats.
+    pub(crate) fn new(stats: Arc<SseStats>) -> Self {
+        Self { stats }
+    }
+
+    /// Get the number of bytes consumed, including comments and newlines.
+    pub fn bytes(&self) -> u64 {
+        self.stats.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of events dispatched.
+    pub fn events(&self) -> u64 {
+        self.stats.events.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,1232 @@
+//! Combinators for streams of sse events.
+
+#[cfg(feature = "decoder")]
+use crate::FromSseEvent;
+#[cfg(all(feature = "decoder", feature = "io"))]
+use crate::SpillFrame;
+#[cfg(all(feature = "decoder", feature = "io"))]
+use crate::SpillItem;
+#[cfg(all(feature = "decoder", feature = "io"))]
+use crate::SpillingSseCodec;
+#[cfg(feature = "io")]
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+#[cfg(feature = "decoder")]
+use crate::SseMiddleware;
+use futures_util::future;
+use futures_util::stream::Stream;
+use futures_util::StreamExt;
+use futures_util::TryStreamExt;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+#[cfg(all(feature = "decoder", feature = "io"))]
+use std::path::PathBuf;
+#[cfg(feature = "time")]
+use std::time::Duration;
+#[cfg(feature = "io")]
+use tokio::io::AsyncRead;
+#[cfg(all(feature = "decoder", feature = "io"))]
+use tokio::io::AsyncSeekExt;
+#[cfg(all(feature = "decoder", feature = "io"))]
+use tokio::io::AsyncWriteExt;
+#[cfg(feature = "io")]
+use tokio::sync::watch;
+#[cfg(feature = "time")]
+use tokio::time::Instant;
+#[cfg(feature = "io")]
+use tokio_util::codec::FramedRead;
+
+/// End a stream once an event with the given name is seen.
+///
+/// If `inclusive` is true, the matching event is yielded before the stream ends.
+/// Errors are passed through.
+pub fn until_event<S, E>(
+    stream: S,
+    name: impl Into<String>,
+    inclusive: bool,
+) -> impl Stream<Item = Result<SseEvent, E>>
+where
+    S: Stream<Item = Result<SseEvent, E>>,
+{
+    let name = name.into();
+
+    stream.scan(false, move |done, item| {
+        if *done {
+            return future::ready(None);
+        }
+
+        let is_end = matches!(&item, Ok(event) if event.event.as_deref() == Some(name.as_str()));
+        if is_end {
+            *done = true;
+            if !inclusive {
+                return future::ready(None);
+            }
+        }
+
+        future::ready(Some(item))
+    })
+}
+
+/// Call a function when the ids of a stream skip a number.
+///
+/// Ids are parsed as sequential integers, and `on_gap` is called with the previous and current id
+/// whenever the current id is not exactly one more than the previous one.
+/// Events without an id, or with an id that is not an integer, are ignored.
+/// Events and errors are passed through unchanged.
+pub fn detect_id_gaps<S, E, F>(stream: S, mut on_gap: F) -> impl Stream<Item = Result<SseEvent, E>>
+where
+    S: Stream<Item = Result<SseEvent, E>>,
+    F: FnMut(u64, u64),
+{
+    let mut previous_id: Option<u64> = None;
+
+    stream.inspect(move |item| {
+        let id = match item
+            .as_ref()
+            .ok()
+            .and_then(|event| event.id.as_deref())
+            .and_then(|id| id.parse::<u64>().ok())
+        {
+            Some(id) => id,
+            None => return,
+        };
+
+        if let Some(previous_id) = previous_id {
+            if previous_id.checked_add(1) != Some(id) {
+                on_gap(previous_id, id);
+            }
+        }
+        previous_id = Some(id);
+    })
+}
+
+/// Drop events up to and including the first event with the given id, then yield everything after it.
+///
+/// This deduplicates events replayed by a server when resuming from a last event id.
+/// If the id is never seen, the stream yields nothing if `emit_all_if_missing` is false,
+/// or all events once the stream ends if it is true.
+/// In the second case, events are buffered until the id is seen, so this should only be used with finite streams.
+/// Errors are passed through.
+pub fn skip_until_id<S, E>(
+    stream: S,
+    id: impl Into<String>,
+    emit_all_if_missing: bool,
+) -> impl Stream<Item = Result<SseEvent, E>>
+where
+    S: Stream<Item = Result<SseEvent, E>>,
+{
+    let id = id.into();
+
+    // Mark the end of the stream with None, so buffered events can be flushed.
+    let stream = stream
+        .map(Some)
+        .chain(futures_util::stream::once(future::ready(None)));
+
+    let mut found = false;
+    let mut skipped = Vec::new();
+    stream
+        .map(move |item| {
+            let items = match item {
+                Some(Ok(event)) if !found => {
+                    if event.id.as_deref() == Some(id.as_str()) {
+                        found = true;
+                        skipped.clear();
+                    } else if emit_all_if_missing {
+                        skipped.push(Ok(event));
+                    }
+                    Vec::new()
+                }
+                Some(item) => vec![item],
+                None => std::mem::take(&mut skipped),
+            };
+
+            futures_util::stream::iter(items)
+        })
+        .flatten()
+}
+
+/// Collect the events between a `begin` and an `end` event into groups.
+///
+/// Each group is yielded as a single item, without the marker events.
+/// Events outside of a group are dropped.
+/// A group is unterminated if the stream ends, or another `begin` event is seen, before its `end` event.
+/// Unterminated groups are yielded if `emit_partial` is true, and dropped otherwise.
+/// Errors are passed through.
+pub fn group_between<S, E>(
+    stream: S,
+    begin: impl Into<String>,
+    end: impl Into<String>,
+    emit_partial: bool,
+) -> impl Stream<Item = Result<Vec<SseEvent>, E>>
+where
+    S: Stream<Item = Result<SseEvent, E>>,
+{
+    let begin = begin.into();
+    let end = end.into();
+
+    // Mark the end of the stream with None, so an unterminated group can be flushed.
+    let stream = stream
+        .map(Some)
+        .chain(futures_util::stream::once(future::ready(None)));
+
+    let mut group: Option<Vec<SseEvent>> = None;
+    stream.filter_map(move |item| {
+        let item = match item {
+            Some(Ok(event)) => {
+                let name = event.event.as_deref();
+                if name == Some(begin.as_str()) {
+                    group.replace(Vec::new()).filter(|_| emit_partial).map(Ok)
+                } else if name == Some(end.as_str()) {
+                    group.take().map(Ok)
+                } else {
+                    if let Some(group) = group.as_mut() {
+                        group.push(event);
+                    }
+                    None
+                }
+            }
+            Some(Err(error)) => Some(Err(error)),
+            None => group.take().filter(|_| emit_partial).map(Ok),
+        };
+
+        future::ready(item)
+    })
+}
+
+/// Drop events with an id that was already seen.
+///
+/// This removes events replayed by a server after a reconnect.
+/// Only the `capacity` most recently seen ids are remembered, so memory use is bounded.
+/// Events without an id, and errors, are passed through.
+pub fn dedup_by_id<S, E>(stream: S, capacity: usize) -> impl Stream<Item = Result<SseEvent, E>>
+where
+    S: Stream<Item = Result<SseEvent, E>>,
+{
+    let mut seen = RecentIds::new(capacity);
+
+    stream.filter(move |item| {
+        let keep = match item.as_ref().ok().and_then(|event| event.id.as_deref()) {
+            Some(id) => !seen.insert(id),
+            None => true,
+        };
+
+        future::ready(keep)
+    })
+}
+
+/// A bounded set of the most recently used ids.
+struct RecentIds {
+    capacity: usize,
+
+    /// The number of the last use of each id.
+    ids: HashMap<String, u64>,
+
+    /// Ids in order of use, oldest first.
+    ///
+    /// An entry is stale if the id was used again later.
+    order: VecDeque<(String, u64)>,
+
+    /// The number of the next use.
+    next_use: u64,
+}
+
+impl RecentIds {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ids: HashMap::new(),
+            order: VecDeque::new(),
+            next_use: 0,
+        }
+    }
+
+    /// Mark an id as used, returning true if it was already in the set.
+    fn insert(&mut self, id: &str) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+
+        let next_use = self.next_use;
+        self.next_use += 1;
+        self.order.push_back((id.to_string(), next_use));
+        let seen = self.ids.insert(id.to_string(), next_use).is_some();
+
+        // Evict the least recently used ids.
+        while self.ids.len() > self.capacity {
+            if let Some((id, last_use)) = self.order.pop_front() {
+                if self.ids.get(&id) == Some(&last_use) {
+                    self.ids.remove(&id);
+                }
+            }
+        }
+
+        // Drop stale entries, so repeated ids don't grow the queue forever.
+        if self.order.len() > self.capacity * 2 {
+            let ids = &self.ids;
+            self.order
+                .retain(|(id, last_use)| ids.get(id) == Some(last_use));
+        }
+
+        seen
+    }
+}
+
+/// An event from [`merge_sources_by_id`]
+#[derive(Debug, PartialEq)]
+pub struct OrderedEvent {
+    /// The event
+    pub event: SseEvent,
+
+    /// Whether the event arrived after an event with a higher id was already yielded.
+    ///
+    /// Late events are yielded immediately, out of order.
+    pub late: bool,
+}
+
+/// Events buffered to be yielded in order of their numeric ids
+///
+/// Each event is stored with extra data, like its arrival time.
+struct IdReorderBuffer<T> {
+    /// Buffered events, keyed by id and then arrival order.
+    events: BTreeMap<(u64, u64), (SseEvent, T)>,
+
+    /// The number of events buffered so far.
+    arrivals: u64,
+
+    /// The id of the last yielded event.
+    last_id: Option<u64>,
+
+    /// The maximum number of buffered events.
+    window: usize,
+}
+
+impl<T> IdReorderBuffer<T> {
+    /// Make a new buffer.
+    fn new(window: usize) -> Self {
+        Self {
+            events: BTreeMap::new(),
+            arrivals: 0,
+            last_id: None,
+            window,
+        }
+    }
+
+    /// Add an event, returning the events that are ready to be yielded.
+    fn push(&mut self, event: SseEvent, extra: T) -> Vec<OrderedEvent> {
+        let mut ready = Vec::new();
+        match event.id.as_deref().and_then(|id| id.parse::<u64>().ok()) {
+            Some(id) if self.last_id.is_some_and(|last_id| id < last_id) => {
+                ready.push(OrderedEvent { event, late: true });
+            }
+            Some(id) => {
+                self.events.insert((id, self.arrivals), (event, extra));
+                self.arrivals += 1;
+            }
+            None => ready.push(OrderedEvent { event, late: false }),
+        }
+
+        while self.events.len() > self.window {
+            ready.extend(self.pop());
+        }
+
+        ready
+    }
+
+    /// Remove the buffered event with the lowest id.
+    fn pop(&mut self) -> Option<OrderedEvent> {
+        let ((id, _), (event, _)) = self.events.pop_first()?;
+        self.last_id = Some(id);
+
+        Some(OrderedEvent { event, late: false })
+    }
+}
+
+/// Merge multiple streams, yielding events in order of their numeric ids.
+///
+/// Up to `window` events are buffered, and the event with the lowest id is yielded once the buffer is full.
+/// A larger window tolerates more reordering between sources, at the cost of latency and memory.
+/// Events that arrive after a higher id was yielded are yielded immediately, and marked as late.
+/// Events without an integer id are yielded immediately.
+/// Buffered events are yielded in order once all sources end.
+/// Errors are passed through.
+/// See [`merge_sources_by_id_with_max_hold`] to also bound how long an event is buffered.
+pub fn merge_sources_by_id<S, E>(
+    sources: Vec<S>,
+    window: usize,
+) -> impl Stream<Item = Result<OrderedEvent, E>>
+where
+    S: Stream<Item = Result<SseEvent, E>> + Unpin,
+{
+    // Mark the end of the sources with None, so buffered events can be flushed.
+    let stream = futures_util::stream::select_all(sources)
+        .map(Some)
+        .chain(futures_util::stream::once(future::ready(None)));
+
+    let mut buffer = IdReorderBuffer::new(window);
+    stream
+        .map(move |item| {
+            let items: Vec<_> = match item {
+                Some(Ok(event)) => buffer.push(event, ()).into_iter().map(Ok).collect(),
+                Some(Err(error)) => vec![Err(error)],
+                None => std::iter::from_fn(|| buffer.pop()).map(Ok).collect(),
+            };
+
+            futures_util::stream::iter(items)
+        })
+        .flatten()
+}
+
+/// Merge multiple streams in order of their numeric ids, like [`merge_sources_by_id`], holding each event for at most `max_hold`.
+///
+/// Once an event has been buffered for `max_hold`, it is yielded along with all buffered events with lower ids,
+/// so a quiet source does not delay the other sources indefinitely.
+#[cfg(feature = "time")]
+pub fn merge_sources_by_id_with_max_hold<S, E>(
+    sources: Vec<S>,
+    window: usize,
+    max_hold: Duration,
+) -> impl Stream<Item = Result<OrderedEvent, E>>
+where
+    S: Stream<Item = Result<SseEvent, E>> + Unpin,
+{
+    let stream = futures_util::stream::select_all(sources);
+    let buffer: IdReorderBuffer<Instant> = IdReorderBuffer::new(window);
+
+    // Items ready to be yielded, and whether the sources ended.
+    let ready = VecDeque::new();
+    let done = false;
+
+    futures_util::stream::unfold(
+        (stream, buffer, ready, done),
+        move |(mut stream, mut buffer, mut ready, mut done)| async move {
+            loop {
+                if let Some(item) = ready.pop_front() {
+                    return Some((item, (stream, buffer, ready, done)));
+                }
+                if done {
+                    return None;
+                }
+
+                let oldest_arrival = buffer.events.values().map(|(_, arrival)| *arrival).min();
+                let item = match oldest_arrival {
+                    Some(arrival) => {
+                        match tokio::time::timeout_at(arrival + max_hold, stream.next()).await {
+                            Ok(item) => item,
+                            Err(_) => {
+                                let now = Instant::now();
+                                while buffer
+                                    .events
+                                    .values()
+                                    .any(|(_, arrival)| *arrival + max_hold <= now)
+                                {
+                                    ready.extend(buffer.pop().map(Ok));
+                                }
+                                continue;
+                            }
+                        }
+                    }
+                    None => stream.next().await,
+                };
+
+                match item {
+                    Some(Ok(event)) => {
+                        ready.extend(buffer.push(event, Instant::now()).into_iter().map(Ok));
+                    }
+                    Some(Err(error)) => ready.push_back(Err(error)),
+                    None => {
+                        ready.extend(std::iter::from_fn(|| buffer.pop()).map(Ok));
+                        done = true;
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Apply a chain of middlewares to each event of a stream, in order.
+///
+/// If a middleware returns `None`, the event is dropped, and later middlewares are not called.
+/// Errors are passed through.
+#[cfg(feature = "decoder")]
+pub fn apply_middleware<S, E>(
+    stream: S,
+    mut middlewares: Vec<Box<dyn SseMiddleware + Send>>,
+) -> impl Stream<Item = Result<SseEvent, E>>
+where
+    S: Stream<Item = Result<SseEvent, E>>,
+{
+    stream.filter_map(move |item| {
+        let item = match item {
+            Ok(event) => middlewares
+                .iter_mut()
+                .try_fold(event, |event, middleware| middleware.transform(event))
+                .map(Ok),
+            Err(error) => Some(Err(error)),
+        };
+
+        future::ready(item)
+    })
+}
+
+/// What to do with an error in [`recover_with`]
+#[derive(Debug, PartialEq)]
+pub enum RecoverAction {
+    /// Drop the error, and continue with the rest of the stream.
+    Skip,
+
+    /// End the stream.
+    Stop,
+
+    /// Replace the error with an event, and continue with the rest of the stream.
+    Emit(SseEvent),
+}
+
+/// Handle the errors of a stream, deciding whether to skip them, stop, or substitute an event.
+///
+/// This only recovers if the stream continues after an error.
+/// A [`FramedRead`](tokio_util::codec::FramedRead) ends after any error,
+/// so recoverable decoding errors should be avoided with options like [`SseCodec::with_utf8_recovery`](crate::SseCodec::with_utf8_recovery) instead.
+pub fn recover_with<S, E, F>(stream: S, mut recover: F) -> impl Stream<Item = SseEvent>
+where
+    S: Stream<Item = Result<SseEvent, E>>,
+    F: FnMut(&E) -> RecoverAction,
+{
+    stream
+        .scan((), move |_, item| {
+            let item = match item {
+                Ok(event) => Some(Some(event)),
+                Err(error) => match recover(&error) {
+                    RecoverAction::Skip => Some(None),
+                    RecoverAction::Stop => None,
+                    RecoverAction::Emit(event) => Some(Some(event)),
+                },
+            };
+
+            future::ready(item)
+        })
+        .filter_map(future::ready)
+}
+
+/// Collect all events of a finite stream.
+///
+/// Returns all events once the stream ends, or the first error.
+/// A partial event at the end of the stream is discarded, per spec.
+pub async fn collect_all<S, E>(stream: S) -> Result<Vec<SseEvent>, E>
+where
+    S: Stream<Item = Result<SseEvent, E>>,
+{
+    stream.try_collect().await
+}
+
+/// An item from [`with_heartbeat`]
+#[cfg(feature = "time")]
+#[derive(Debug, PartialEq)]
+pub enum HeartbeatItem {
+    /// An event from the stream.
+    Event(SseEvent),
+
+    /// No event arrived within the interval.
+    Heartbeat,
+}
+
+/// Yield a heartbeat each time no event arrives within `interval`.
+///
+/// Unlike a timeout, the stream keeps going after a heartbeat.
+/// The interval restarts after each item, including heartbeats and errors.
+/// Errors are passed through.
+#[cfg(feature = "time")]
+pub fn with_heartbeat<S, E>(
+    stream: S,
+    interval: Duration,
+) -> impl Stream<Item = Result<HeartbeatItem, E>>
+where
+    S: Stream<Item = Result<SseEvent, E>> + Unpin,
+{
+    futures_util::stream::unfold(stream, move |mut stream| async move {
+        let deadline = Instant::now() + interval;
+        let item = match tokio::time::timeout_at(deadline, stream.next()).await {
+            Ok(item) => item?.map(HeartbeatItem::Event),
+            Err(_) => Ok(HeartbeatItem::Heartbeat),
+        };
+
+        Some((item, stream))
+    })
+}
+
+/// Convert each event into a typed value.
+///
+/// Conversion errors are yielded as errors, without ending the stream.
+#[cfg(feature = "decoder")]
+pub fn typed_events<T, S, E>(stream: S) -> impl Stream<Item = Result<T, E>>
+where
+    S: Stream<Item = Result<SseEvent, E>>,
+    T: FromSseEvent,
+    E: From<SseCodecError>,
+{
+    stream.map(|item| item.and_then(|event| T::from_sse_event(event).map_err(E::from)))
+}
+
+/// An item from [`with_partial_events`]
+#[cfg(all(feature = "decoder", feature = "io", feature = "time"))]
+#[derive(Debug, PartialEq)]
+pub enum ProgressiveEvent {
+    /// A snapshot of an event that has not been dispatched yet.
+    ///
+    /// The data includes only the complete data lines received so far.
+    Partial(SseEvent),
+
+    /// A dispatched event.
+    Complete(SseEvent),
+}
+
+/// Yield snapshots of an event that is slow to be dispatched, for progressive rendering.
+///
+/// If no event is dispatched within `delay`, but data for the next event is buffered,
+/// a snapshot of the partial event is yielded, without consuming it.
+/// Another snapshot is yielded after each further `delay`, but only if more data was received.
+/// The delay restarts after each item.
+#[cfg(all(feature = "decoder", feature = "io", feature = "time"))]
+pub fn with_partial_events<R>(
+    reader: FramedRead<R, SseCodec>,
+    delay: Duration,
+) -> impl Stream<Item = Result<ProgressiveEvent, SseCodecError>>
+where
+    R: AsyncRead + Unpin,
+{
+    // The data length of the last snapshot, so unchanged snapshots are skipped.
+    let last_partial_len: Option<usize> = None;
+
+    futures_util::stream::unfold(
+        (reader, last_partial_len),
+        move |(mut reader, mut last_partial_len)| async move {
+            loop {
+                let deadline = Instant::now() + delay;
+                match tokio::time::timeout_at(deadline, reader.next()).await {
+                    Ok(item) => {
+                        last_partial_len = None;
+                        let item = item?.map(ProgressiveEvent::Complete);
+                        return Some((item, (reader, last_partial_len)));
+                    }
+                    Err(_) => {
+                        let view = reader.decoder().in_progress();
+                        if view.data_len.is_none() || view.data_len == last_partial_len {
+                            continue;
+                        }
+                        last_partial_len = view.data_len;
+
+                        let event = SseEvent {
+                            event: view.event.map(String::from),
+                            data: view.data.map(String::from),
+                            id: view.id.map(String::from),
+                            retry: view.retry,
+                        };
+                        let item = Ok(ProgressiveEvent::Partial(event));
+                        return Some((item, (reader, last_partial_len)));
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Decode events from a reader, tracking the last event id.
+///
+/// The receiver always holds the latest last event id of the decoder,
+/// including ids from blocks that were not dispatched because they had no data.
+/// It keeps its value after the stream ends, so it can be used to resume the stream with a new connection.
+#[cfg(feature = "io")]
+pub fn events_with_last_id<R>(
+    reader: R,
+) -> (
+    impl Stream<Item = Result<SseEvent, SseCodecError>>,
+    watch::Receiver<Option<String>>,
+)
+where
+    R: AsyncRead + Unpin,
+{
+    let (sender, receiver) = watch::channel(None);
+    let reader = FramedRead::new(reader, SseCodec::new());
+
+    let stream =
+        futures_util::stream::unfold((reader, sender), |(mut reader, sender)| async move {
+            let item = reader.next().await;
+
+            let last_event_id = reader.decoder().last_event_id();
+            sender.send_if_modified(|current| {
+                let modified = current.as_deref() != last_event_id;
+                if modified {
+                    *current = last_event_id.map(String::from);
+                }
+                modified
+            });
+
+            let item = item?;
+            Some((item, (reader, sender)))
+        });
+
+    (stream, receiver)
+}
+
+/// Decode events from a reader, writing large data fields to files.
+///
+/// Spill files are written with [`tokio::fs`], so decoding never blocks the runtime.
+/// The spill file of an event that is not completed, because of an error or the end of the stream, is deleted.
+#[cfg(all(feature = "decoder", feature = "io"))]
+pub fn spill_events<R>(
+    reader: R,
+    codec: SpillingSseCodec,
+) -> impl Stream<Item = Result<SpillItem, SseCodecError>>
+where
+    R: AsyncRead + Unpin,
+{
+    let reader = FramedRead::new(reader, codec);
+    let spill: Option<(tokio::fs::File, PathBuf)> = None;
+
+    futures_util::stream::unfold(Some((reader, spill)), |state| async move {
+        let (mut reader, mut spill) = state?;
+        match next_spill_item(&mut reader, &mut spill).await {
+            Ok(Some(item)) => Some((Ok(item), Some((reader, spill)))),
+            result => {
+                if let Some((file, path)) = spill.take() {
+                    drop(file);
+                    let _ = tokio::fs::remove_file(path).await;
+                }
+                result.transpose().map(|item| (item, None))
+            }
+        }
+    })
+}
+
+/// Decode the next item for [`spill_events`], writing spilled data to the spill file.
+#[cfg(all(feature = "decoder", feature = "io"))]
+async fn next_spill_item<R>(
+    reader: &mut FramedRead<R, SpillingSseCodec>,
+    spill: &mut Option<(tokio::fs::File, PathBuf)>,
+) -> Result<Option<SpillItem>, SseCodecError>
+where
+    R: AsyncRead + Unpin,
+{
+    while let Some(frame) = reader.next().await.transpose()? {
+        match frame {
+            SpillFrame::Event(event) => return Ok(Some(SpillItem::Event(event))),
+            SpillFrame::Data(chunk) => {
+                let (file, _) = match spill.as_mut() {
+                    Some(spill) => spill,
+                    None => {
+                        let path = reader.decoder().new_spill_path();
+                        let file = tokio::fs::OpenOptions::new()
+                            .read(true)
+                            .write(true)
+                            .create_new(true)
+                            .open(&path)
+                            .await?;
+                        spill.insert((file, path))
+                    }
+                };
+                file.write_all(chunk.as_bytes()).await?;
+            }
+            SpillFrame::End(mut event) => {
+                let (mut file, path) = spill
+                    .take()
+                    .expect("spilled event is missing its spill file");
+                if let Some(data) = event.data.take() {
+                    file.write_all(data.as_bytes()).await?;
+                }
+                file.flush().await?;
+                file.rewind().await?;
+                let file = file.into_std().await;
+
+                return Ok(Some(SpillItem::Spilled { event, file, path }));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SseCodec;
+    use tokio_util::codec::FramedRead;
+
+    const TEST_DATA: &str = "data: a\n\nevent: done\ndata: b\n\ndata: c\n\n";
+
+    #[tokio::test]
+    async fn until_event_inclusive() {
+        let reader = FramedRead::new(TEST_DATA.as_bytes(), SseCodec::new());
+        let events: Vec<_> = until_event(reader, "done", true)
+            .map(|event| event.expect("failed to parse").data)
+            .collect()
+            .await;
+        assert!(events == [Some("a".into()), Some("b".into())]);
+    }
+
+    #[tokio::test]
+    async fn until_event_exclusive() {
+        let reader = FramedRead::new(TEST_DATA.as_bytes(), SseCodec::new());
+        let events: Vec<_> = until_event(reader, "done", false)
+            .map(|event| event.expect("failed to parse").data)
+            .collect()
+            .await;
+        assert!(events == [Some("a".into())]);
+    }
+
+    #[tokio::test]
+    async fn collect_all_events() {
+        let reader = FramedRead::new(
+            &b"data: a\n\nevent: b\ndata: b\n\ndata: partial\n"[..],
+            SseCodec::new(),
+        );
+        let events = collect_all(reader).await.expect("failed to parse");
+        assert!(
+            events
+                == [
+                    SseEvent::new().with_data("a"),
+                    SseEvent::new().with_event("b").with_data("b")
+                ]
+        );
+
+        let reader = FramedRead::new(
+            &b"data: a\n\ndata: \xFF\n\ndata: c\n\n"[..],
+            SseCodec::new(),
+        );
+        let error = collect_all(reader).await.expect_err("missing error");
+        assert!(matches!(
+            error,
+            crate::SseCodecError::InvalidUtf8 { line: 3, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn id_gaps() {
+        let test_data = "id: 1\ndata: a\n\nid: 2\ndata: b\n\ndata: c\n\nid: x\ndata: d\n\nid: 4\ndata: e\n\nid: 5\ndata: f\n\n";
+        let reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+
+        let mut gaps = Vec::new();
+        let events = collect_all(detect_id_gaps(reader, |previous, current| {
+            gaps.push((previous, current))
+        }))
+        .await
+        .expect("failed to parse");
+
+        assert!(events.len() == 6);
+        assert!(gaps == [(2, 4)]);
+    }
+
+    #[tokio::test]
+    async fn skip_replayed_prefix() {
+        let test_data = "id: 1\ndata: a\n\nid: 2\ndata: b\n\ndata: c\n\nid: 3\ndata: d\n\n";
+
+        let reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let events: Vec<_> = skip_until_id(reader, "2", false)
+            .map(|event| event.expect("failed to parse").data)
+            .collect()
+            .await;
+        assert!(events == [Some("c".into()), Some("d".into())]);
+
+        let reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let events = collect_all(skip_until_id(reader, "4", false))
+            .await
+            .expect("failed to parse");
+        assert!(events.is_empty());
+
+        let reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let events: Vec<_> = skip_until_id(reader, "4", true)
+            .map(|event| event.expect("failed to parse").data)
+            .collect()
+            .await;
+        assert!(
+            events
+                == [
+                    Some("a".into()),
+                    Some("b".into()),
+                    Some("c".into()),
+                    Some("d".into())
+                ]
+        );
+    }
+
+    #[tokio::test]
+    async fn dedup() {
+        let test_data = "id: 1\ndata: a\n\nid: 2\ndata: b\n\ndata: no id\n\nid: 1\ndata: a\n\nid: 2\ndata: b\n\nid: 3\ndata: c\n\ndata: no id\n\n";
+
+        let reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let events: Vec<_> = dedup_by_id(reader, 16)
+            .map(|event| event.expect("failed to parse").data)
+            .collect()
+            .await;
+        assert!(
+            events
+                == [
+                    Some("a".into()),
+                    Some("b".into()),
+                    Some("no id".into()),
+                    Some("c".into()),
+                    Some("no id".into())
+                ]
+        );
+
+        // Only the last id is remembered, so the replayed 1 is forgotten once 2 is seen.
+        let reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let events: Vec<_> = dedup_by_id(reader, 1)
+            .map(|event| event.expect("failed to parse").id)
+            .collect()
+            .await;
+        assert!(
+            events
+                == [
+                    Some("1".into()),
+                    Some("2".into()),
+                    None,
+                    Some("1".into()),
+                    Some("2".into()),
+                    Some("3".into()),
+                    None
+                ]
+        );
+    }
+
+    #[test]
+    fn recent_ids() {
+        let mut ids = RecentIds::new(2);
+        assert!(!ids.insert("a"));
+        assert!(!ids.insert("b"));
+        assert!(ids.insert("a"));
+
+        // b is the least recently used, so it is evicted.
+        assert!(!ids.insert("c"));
+        assert!(ids.insert("a"));
+        assert!(!ids.insert("b"));
+
+        for _ in 0..100 {
+            ids.insert("b");
+        }
+        assert!(ids.order.len() <= 4);
+    }
+
+    #[tokio::test]
+    async fn merge_by_id() {
+        let source =
+            |ids: &'static [u64]| {
+                futures_util::stream::iter(ids.iter().map(|id| {
+                    Ok::<_, crate::SseCodecError>(SseEvent::new().with_id(id.to_string()))
+                }))
+            };
+        let sources = vec![
+            source(&[1, 4, 5, 9]),
+            source(&[2, 3, 8]),
+            source(&[6, 7, 0]),
+        ];
+
+        let events: Vec<_> = merge_sources_by_id(sources, 3)
+            .map(|event| {
+                let event = event.expect("failed to parse");
+                (event.event.id.expect("missing id"), event.late)
+            })
+            .collect()
+            .await;
+        let expected: Vec<_> = [
+            ("1", false),
+            ("2", false),
+            ("3", false),
+            ("4", false),
+            ("5", false),
+            ("0", true),
+            ("6", false),
+            ("7", false),
+            ("8", false),
+            ("9", false),
+        ]
+        .into_iter()
+        .map(|(id, late)| (id.to_string(), late))
+        .collect();
+        assert!(events == expected);
+    }
+
+    #[cfg(feature = "time")]
+    #[tokio::test(start_paused = true)]
+    async fn merge_by_id_max_hold() {
+        let source = |events: &'static [(u64, u64)]| {
+            Box::pin(
+                futures_util::stream::iter(events).then(|(delay, id)| async move {
+                    tokio::time::sleep(Duration::from_millis(*delay)).await;
+                    Ok::<_, crate::SseCodecError>(SseEvent::new().with_id(id.to_string()))
+                }),
+            )
+        };
+        // Events are sent at 0ms and 500ms, and at 50ms and 300ms, as (delay, id).
+        let sources = vec![source(&[(0, 2), (500, 5)]), source(&[(50, 1), (250, 3)])];
+
+        let start = Instant::now();
+        let events: Vec<_> =
+            merge_sources_by_id_with_max_hold(sources, 10, Duration::from_millis(100))
+                .map(|event| {
+                    let event = event.expect("failed to parse");
+                    assert!(!event.late);
+                    let elapsed = start.elapsed().as_millis();
+                    (elapsed, event.event.id.expect("missing id"))
+                })
+                .collect()
+                .await;
+
+        // The window is never full, so events are only yielded when held for too long, or at the end.
+        // Event 1 is yielded with event 2, since it has a lower id.
+        let expected: Vec<_> = [(100, "1"), (100, "2"), (400, "3"), (500, "5")]
+            .into_iter()
+            .map(|(elapsed, id)| (elapsed, id.to_string()))
+            .collect();
+        assert!(events == expected);
+    }
+
+    #[tokio::test]
+    async fn groups() {
+        let test_data = "data: outside\n\nevent: begin\ndata: \n\ndata: a\n\ndata: b\n\nevent: end\ndata: \n\nevent: begin\ndata: \n\ndata: c\n\nevent: end\ndata: \n\nevent: begin\ndata: \n\ndata: d\n\n";
+        let data = |groups: Vec<Vec<SseEvent>>| -> Vec<Vec<Option<String>>> {
+            groups
+                .into_iter()
+                .map(|group| group.into_iter().map(|event| event.data).collect())
+                .collect()
+        };
+
+        let reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let groups = collect_groups(group_between(reader, "begin", "end", false)).await;
+        assert!(
+            data(groups)
+                == [
+                    vec![Some("a".into()), Some("b".into())],
+                    vec![Some("c".into())]
+                ]
+        );
+
+        let reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+        let groups = collect_groups(group_between(reader, "begin", "end", true)).await;
+        assert!(
+            data(groups)
+                == [
+                    vec![Some("a".into()), Some("b".into())],
+                    vec![Some("c".into())],
+                    vec![Some("d".into())]
+                ]
+        );
+    }
+
+    /// Collect all groups of a stream.
+    async fn collect_groups<S>(stream: S) -> Vec<Vec<SseEvent>>
+    where
+        S: Stream<Item = Result<Vec<SseEvent>, crate::SseCodecError>>,
+    {
+        stream.try_collect().await.expect("failed to parse")
+    }
+
+    #[cfg(all(feature = "decoder", feature = "io"))]
+    #[tokio::test]
+    async fn spill() {
+        use std::io::Read;
+
+        let dir = std::env::temp_dir();
+        let test_data = "data: small\n\nevent: big\ndata: 0123456789\ndata: abcdefghij\n\ndata: 0123456789\ndata: 0123456789\ndata: x\n\ndata: partial 0123456789\n";
+        let codec = SseCodec::new().with_spill_threshold(16, &dir);
+        let items: Vec<_> = spill_events(test_data.as_bytes(), codec)
+            .map(|item| item.expect("failed to parse"))
+            .collect()
+            .await;
+
+        assert!(items.len() == 3);
+        assert!(
+            matches!(&items[0], SpillItem::Event(event) if *event == SseEvent::new().with_data("small"))
+        );
+
+        let expected = [
+            (SseEvent::new().with_event("big"), "0123456789\nabcdefghij"),
+            (SseEvent::new(), "0123456789\n0123456789\nx"),
+        ];
+        for (item, (expected_event, expected_data)) in items.into_iter().skip(1).zip(expected) {
+            let (event, mut file, path) = match item {
+                SpillItem::Spilled { event, file, path } => (event, file, path),
+                SpillItem::Event(_) => panic!("event was not spilled"),
+            };
+            assert!(event == expected_event);
+            assert!(path.starts_with(&dir));
+
+            let mut data = String::new();
+            file.read_to_string(&mut data)
+                .expect("failed to read spill file");
+            drop(file);
+            std::fs::remove_file(path).expect("failed to remove spill file");
+            assert!(data == expected_data);
+        }
+    }
+
+    #[cfg(feature = "io")]
+    #[tokio::test]
+    async fn last_id() {
+        let test_data = "id: 1\ndata: a\n\ndata: b\n\nid: 2\n\n";
+        let (stream, receiver) = events_with_last_id(test_data.as_bytes());
+        let mut stream = std::pin::pin!(stream);
+        assert!(receiver.borrow().is_none());
+
+        let event = stream
+            .next()
+            .await
+            .expect("missing event")
+            .expect("failed to parse");
+        assert!(event.data.as_deref() == Some("a"));
+        assert!(receiver.borrow().as_deref() == Some("1"));
+
+        let event = stream
+            .next()
+            .await
+            .expect("missing event")
+            .expect("failed to parse");
+        assert!(event.data.as_deref() == Some("b"));
+        assert!(receiver.borrow().as_deref() == Some("1"));
+
+        // The last block has no data, but still updates the id.
+        assert!(stream.next().await.is_none());
+        assert!(receiver.borrow().as_deref() == Some("2"));
+    }
+
+    #[cfg(feature = "decoder")]
+    #[tokio::test]
+    async fn middleware_chain() {
+        let test_data = "id: 1\ndata: a\n\nid: 2\nevent: secret\ndata: b\n\nid: 3\ndata: c\n\n";
+        let reader = FramedRead::new(test_data.as_bytes(), SseCodec::new());
+
+        let middlewares: Vec<Box<dyn SseMiddleware + Send>> = vec![
+            Box::new(|event: SseEvent| (event.event.as_deref() != Some("secret")).then_some(event)),
+            Box::new(crate::StripId),
+            Box::new(|event: SseEvent| {
+                let data = event.data.as_deref().unwrap_or_default().to_uppercase();
+                Some(event.with_data(data))
+            }),
+        ];
+        let events = collect_all(apply_middleware(reader, middlewares))
+            .await
+            .expect("failed to parse");
+
+        assert!(
+            events
+                == [
+                    SseEvent::new().with_data("A"),
+                    SseEvent::new().with_data("C")
+                ]
+        );
+    }
+
+    #[tokio::test]
+    async fn recover() {
+        use crate::SseCodecError;
+
+        let items = vec![
+            Ok(SseEvent::new().with_data("a")),
+            Err(SseCodecError::InvalidLengthPrefix),
+            Ok(SseEvent::new().with_data("b")),
+            Err(SseCodecError::UncloneableRequest),
+            Ok(SseEvent::new().with_data("c")),
+            Err(SseCodecError::Io(
+                std::io::ErrorKind::ConnectionReset.into(),
+            )),
+            Ok(SseEvent::new().with_data("d")),
+        ];
+        let events: Vec<_> = recover_with(futures_util::stream::iter(items), |error| match error {
+            SseCodecError::InvalidLengthPrefix => RecoverAction::Skip,
+            SseCodecError::Io(_) => RecoverAction::Stop,
+            _ => RecoverAction::Emit(
+                SseEvent::new()
+                    .with_event("error")
+                    .with_data(error.to_string()),
+            ),
+        })
+        .collect()
+        .await;
+
+        let expected_events = [
+            SseEvent::new().with_data("a"),
+            SseEvent::new().with_data("b"),
+            SseEvent::new()
+                .with_event("error")
+                .with_data("the request could not be cloned"),
+            SseEvent::new().with_data("c"),
+        ];
+        assert!(events == expected_events);
+    }
+
+    #[cfg(feature = "time")]
+    #[tokio::test(start_paused = true)]
+    async fn heartbeat() {
+        // Events arrive 250ms, 300ms and 350ms after the start.
+        let delays = [250, 50, 50];
+        let source = futures_util::stream::iter(delays).then(|delay| async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            Ok::<_, std::convert::Infallible>(SseEvent::new().with_data(delay.to_string()))
+        });
+
+        let items: Vec<_> = with_heartbeat(Box::pin(source), Duration::from_millis(100))
+            .map(|item| item.expect("failed to parse"))
+            .collect()
+            .await;
+        let expected_items = [
+            HeartbeatItem::Heartbeat,
+            HeartbeatItem::Heartbeat,
+            HeartbeatItem::Event(SseEvent::new().with_data("250")),
+            HeartbeatItem::Event(SseEvent::new().with_data("50")),
+            HeartbeatItem::Event(SseEvent::new().with_data("50")),
+        ];
+        assert!(items == expected_items);
+    }
+
+    #[cfg(feature = "derive")]
+    #[tokio::test]
+    async fn typed() {
+        #[derive(Debug, PartialEq, crate::FromSseEvent)]
+        enum Message {
+            #[sse(event = "done")]
+            Done,
+
+            #[sse(event = "message")]
+            Text(String),
+        }
+
+        let reader = FramedRead::new(TEST_DATA.as_bytes(), SseCodec::new());
+        let messages: Vec<Message> = typed_events(reader)
+            .map(|message| message.expect("failed to convert"))
+            .collect()
+            .await;
+        let expected_messages = [
+            Message::Text("a".into()),
+            Message::Done,
+            Message::Text("c".into()),
+        ];
+        assert!(messages == expected_messages);
+    }
+
+    #[cfg(all(feature = "decoder", feature = "io", feature = "time"))]
+    #[tokio::test(start_paused = true)]
+    async fn partial_events() {
+        use tokio::io::AsyncWriteExt;
+
+        let (reader, mut writer) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            writer.write_all(b"event: slow\ndata: a\n").await.unwrap();
+            tokio::time::sleep(Duration::from_millis(350)).await;
+            writer.write_all(b"data: b\n\ndata: c\n\n").await.unwrap();
+        });
+
+        let reader = FramedRead::new(reader, SseCodec::new());
+        let items: Vec<_> = with_partial_events(reader, Duration::from_millis(100))
+            .map(|item| item.expect("failed to parse"))
+            .collect()
+            .await;
+
+        // Only one snapshot is yielded while the data does not change.
+        let expected_items = [
+            ProgressiveEvent::Partial(SseEvent::new().with_event("slow").with_data("a")),
+            ProgressiveEvent::Complete(SseEvent::new().with_event("slow").with_data("a\nb")),
+            ProgressiveEvent::Complete(SseEvent::new().with_data("c")),
+        ];
+        assert!(items == expected_items);
+    }
+}
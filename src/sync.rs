@@ -0,0 +1,133 @@
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use std::io::BufRead;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+impl SseCodec {
+    /// Decode events from a synchronous buffered reader.
+    ///
+    /// Complete lines are decoded directly from the reader's buffer, using [`BufRead::fill_buf`] and [`BufRead::consume`].
+    /// Only a partial line at the end of the reader's buffer is copied, up to its newline,
+    /// and later lines are decoded from the reader's buffer again.
+    /// The reader is only read when more bytes are needed for the next event.
+    /// The iterator ends after the first error.
+    pub fn read_bufread<R>(
+        mut self,
+        mut reader: R,
+    ) -> impl Iterator<Item = Result<SseEvent, SseCodecError>>
+    where
+        R: BufRead,
+    {
+        // A partial line that spans multiple reads of the reader.
+        let mut pending = BytesMut::new();
+        let mut done = false;
+
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+
+            let result = loop {
+                if !pending.is_empty() {
+                    match self.decode(&mut pending) {
+                        Ok(Some(event)) => break Ok(Some(event)),
+                        Ok(None) => {}
+                        Err(error) => break Err(error),
+                    }
+                }
+
+                let chunk = match reader.fill_buf() {
+                    Ok(chunk) => chunk,
+                    Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(error) => break Err(error.into()),
+                };
+
+                if chunk.is_empty() {
+                    break self.decode_eof(&mut pending);
+                }
+
+                let len = if pending.is_empty() {
+                    match self.decode_slice(chunk) {
+                        Ok((Some(event), n)) => {
+                            reader.consume(n);
+                            break Ok(Some(event));
+                        }
+                        Ok((None, n)) => {
+                            pending.extend_from_slice(&chunk[n..]);
+                            chunk.len()
+                        }
+                        Err(error) => break Err(error),
+                    }
+                } else {
+                    // Copy only the rest of the partial line, so the pending buffer empties once it is decoded.
+                    let len = chunk
+                        .iter()
+                        .position(|b| matches!(b, b'\r' | b'\n'))
+                        .map_or(chunk.len(), |index| index + 1);
+                    pending.extend_from_slice(&chunk[..len]);
+                    len
+                };
+                reader.consume(len);
+            };
+
+            match result {
+                Ok(Some(event)) => Some(Ok(event)),
+                Ok(None) => {
+                    done = true;
+                    None
+                }
+                Err(error) => {
+                    done = true;
+                    Some(Err(error))
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn bufread() {
+        let test_data = "event: a\ndata: 1\n\ndata: a long line that spans reads\r\n\r\n: comment\ndata: 3\r\rdata: partial";
+
+        for capacity in [1, 2, 7, 64] {
+            let reader = BufReader::with_capacity(capacity, test_data.as_bytes());
+            let events: Vec<_> = SseCodec::new()
+                .read_bufread(reader)
+                .map(|event| event.expect("failed to parse"))
+                .collect();
+            assert!(
+                events
+                    == [
+                        SseEvent::new().with_event("a").with_data("1"),
+                        SseEvent::new().with_data("a long line that spans reads"),
+                        SseEvent::new().with_data("3"),
+                    ],
+                "{capacity}"
+            );
+        }
+    }
+
+    #[test]
+    fn bufread_error() {
+        let reader = BufReader::new(&b"data: a\n\ndata: \xFF\n\ndata: c\n\n"[..]);
+        let mut events = SseCodec::new().read_bufread(reader);
+
+        let event = events
+            .next()
+            .expect("missing event")
+            .expect("failed to parse");
+        assert!(event.data.as_deref() == Some("a"));
+        assert!(matches!(
+            events.next(),
+            Some(Err(SseCodecError::InvalidUtf8 { line: 3, .. }))
+        ));
+        assert!(events.next().is_none());
+    }
+}
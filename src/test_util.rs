@@ -0,0 +1,157 @@
+use crate::SseEvent;
+use crate::SseWriter;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+/// The response head sent before the events.
+const RESPONSE_HEAD: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n";
+
+/// A local SSE server for testing clients
+///
+/// The server accepts one connection at a time, and sends each enqueued event in order, flushing after each one.
+/// If a connection is lost, the next connection continues with the next event.
+/// Once the server is closed and all enqueued events are sent, the connection is closed.
+#[derive(Debug)]
+pub struct TestSseServer {
+    /// The address of the server.
+    addr: SocketAddr,
+
+    /// The event queue, with the delay before each event.
+    events: mpsc::UnboundedSender<(Duration, SseEvent)>,
+}
+
+impl TestSseServer {
+    /// Start a server on a random local port.
+    ///
+    /// This must be called from within a tokio runtime.
+    pub async fn bind() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let (events, receiver) = mpsc::unbounded_channel();
+
+        tokio::spawn(serve(listener, receiver));
+
+        Ok(Self { addr, events })
+    }
+
+    /// Get the address of the server.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Get the url of the server.
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    /// Enqueue an event, to be sent as soon as possible.
+    pub fn enqueue(&self, event: SseEvent) {
+        self.enqueue_after(Duration::ZERO, event);
+    }
+
+    /// Enqueue an event, to be sent after waiting for the given delay once the previous event is sent.
+    pub fn enqueue_after(&self, delay: Duration, event: SseEvent) {
+        // The server task only stops once this sender is dropped.
+        let _ = self.events.send((delay, event));
+    }
+
+    /// Close the server once all enqueued events are sent.
+    pub fn close(self) {}
+}
+
+/// Serve connections until the event queue is closed.
+async fn serve(listener: TcpListener, mut events: mpsc::UnboundedReceiver<(Duration, SseEvent)>) {
+    loop {
+        let socket = match listener.accept().await {
+            Ok((socket, _)) => socket,
+            Err(_) => continue,
+        };
+
+        let mut writer = match respond(socket).await {
+            Ok(writer) => writer,
+            Err(_) => continue,
+        };
+
+        loop {
+            let (delay, event) = match events.recv().await {
+                Some(event) => event,
+                None => {
+                    let _ = writer.into_inner().shutdown().await;
+                    return;
+                }
+            };
+
+            tokio::time::sleep(delay).await;
+            if writer.send_event_flush(&event).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Read the request head and write the response head.
+async fn respond(mut socket: TcpStream) -> std::io::Result<SseWriter<TcpStream>> {
+    let mut request = Vec::new();
+    while !request.ends_with(b"\r\n\r\n") {
+        let mut buffer = [0; 1024];
+        let n = socket.read(&mut buffer).await?;
+        if n == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        request.extend_from_slice(&buffer[..n]);
+    }
+
+    socket.write_all(RESPONSE_HEAD).await?;
+    socket.flush().await?;
+
+    Ok(SseWriter::new(socket))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SseCodec;
+    use tokio_stream::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    #[tokio::test]
+    async fn end_to_end() {
+        let server = TestSseServer::bind().await.expect("failed to bind");
+        server.enqueue(SseEvent::new().with_id("1").with_data("a"));
+        server.enqueue_after(
+            Duration::from_millis(10),
+            SseEvent::new().with_event("b").with_data("b\nc"),
+        );
+        let addr = server.addr();
+        server.close();
+
+        let mut socket = TcpStream::connect(addr).await.expect("failed to connect");
+        socket
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .expect("failed to write");
+
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            let mut byte = [0];
+            socket.read_exact(&mut byte).await.expect("failed to read");
+            head.push(byte[0]);
+        }
+        assert!(head == RESPONSE_HEAD);
+
+        let events: Vec<_> = FramedRead::new(socket, SseCodec::new())
+            .map(|event| event.expect("failed to parse"))
+            .collect()
+            .await;
+        let expected_events = [
+            SseEvent::new().with_id("1").with_data("a"),
+            SseEvent::new().with_event("b").with_data("b\nc"),
+        ];
+        assert!(events == expected_events);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use tokio::time::Instant;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+/// An sse event, along with the time it was decoded
+#[derive(Debug, PartialEq)]
+pub struct TimestampedEvent {
+    /// The decoded event
+    pub event: SseEvent,
+
+    /// The time the event was fully decoded.
+    pub decoded_at: Instant,
+}
+
+/// An sse codec that records the time each event was decoded
+#[derive(Debug, Default)]
+pub struct TimestampedSseCodec {
+    codec: SseCodec,
+}
+
+impl TimestampedSseCodec {
+    /// Make a new timestamped SSE Event decoder.
+    pub fn new() -> Self {
+        Self::with_codec(SseCodec::new())
+    }
+
+    /// Make a new timestamped SSE Event decoder, wrapping the given codec.
+    pub fn with_codec(codec: SseCodec) -> Self {
+        Self { codec }
+    }
+}
+
+impl Decoder for TimestampedSseCodec {
+    type Item = TimestampedEvent;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let event = self.codec.decode(bytes)?;
+        Ok(event.map(|event| TimestampedEvent {
+            event,
+            decoded_at: Instant::now(),
+        }))
+    }
+
+    fn decode_eof(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let event = self.codec.decode_eof(bytes)?;
+        Ok(event.map(|event| TimestampedEvent {
+            event,
+            decoded_at: Instant::now(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn timestamps_advance() {
+        let mut codec = TimestampedSseCodec::new();
+
+        let mut buffer = BytesMut::from("data: a\n\n");
+        let event_1 = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event 1");
+
+        tokio::time::advance(Duration::from_secs(2)).await;
+
+        let mut buffer = BytesMut::from("data: b\n\n");
+        let event_2 = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event 2");
+
+        assert!(event_1.event.data.as_deref() == Some("a"));
+        assert!(event_2.event.data.as_deref() == Some("b"));
+        assert!(event_2.decoded_at - event_1.decoded_at == Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,313 @@
+use crate::LineScanner;
+use crate::SseCodec;
+use crate::SseCodecError;
+use crate::SseEvent;
+use std::collections::VecDeque;
+use tokio_util::bytes::Buf;
+use tokio_util::bytes::BytesMut;
+
+/// The parsing state of a codec, for rolling back a transaction.
+#[derive(Debug)]
+struct Snapshot {
+    lines: LineScanner,
+    event: Option<String>,
+    data: Option<String>,
+    id: Option<String>,
+    retry: Option<u64>,
+    last_event_id: Option<String>,
+    last_retry: Option<u64>,
+    retry_update: Option<u64>,
+    raw: Option<Vec<u8>>,
+    line_ending_pending_cr: bool,
+    event_bytes: usize,
+    stream_bytes: usize,
+    id_history: VecDeque<String>,
+}
+
+impl Snapshot {
+    /// Save the parsing state of a codec.
+    fn new(codec: &SseCodec) -> Self {
+        Self {
+            lines: codec.parser.lines.clone(),
+            event: codec.parser.event.clone(),
+            data: codec.parser.data.clone(),
+            id: codec.parser.id.clone(),
+            retry: codec.parser.retry,
+            last_event_id: codec.parser.last_event_id.clone(),
+            last_retry: codec.parser.last_retry,
+            retry_update: codec.parser.retry_update,
+            raw: codec.parser.raw.clone(),
+            line_ending_pending_cr: codec.parser.line_ending_pending_cr,
+            event_bytes: codec.parser.event_bytes,
+            stream_bytes: codec.parser.stream_bytes,
+            id_history: codec.parser.id_history.clone(),
+        }
+    }
+
+    /// Restore the parsing state of a codec.
+    fn restore(self, codec: &mut SseCodec) {
+        codec.parser.lines = self.lines;
+        codec.parser.event = self.event;
+        codec.parser.data = self.data;
+        codec.parser.id = self.id;
+        codec.parser.retry = self.retry;
+        codec.parser.last_event_id = self.last_event_id;
+        codec.parser.last_retry = self.last_retry;
+        codec.parser.retry_update = self.retry_update;
+        codec.parser.raw = self.raw;
+        codec.parser.line_ending_pending_cr = self.line_ending_pending_cr;
+        codec.parser.event_bytes = self.event_bytes;
+        codec.parser.stream_bytes = self.stream_bytes;
+        codec.parser.id_history = self.id_history;
+    }
+}
+
+/// A batch of decoded events that can be committed or rolled back
+///
+/// Make one with [`SseCodec::decode_transaction`].
+/// The buffer is only advanced on [`Transaction::commit`].
+/// Dropping the transaction without committing it restores the codec,
+/// so the same bytes can be decoded again.
+#[derive(Debug)]
+pub struct Transaction<'a> {
+    codec: &'a mut SseCodec,
+    bytes: &'a mut BytesMut,
+
+    /// The state of the codec before the transaction, or `None` once committed.
+    snapshot: Option<Snapshot>,
+
+    /// The number of bytes consumed, including complete lines of a partial event.
+    consumed: usize,
+
+    events: Vec<SseEvent>,
+}
+
+impl SseCodec {
+    /// Decode all complete events in a buffer, without advancing it.
+    ///
+    /// The events can be validated as a batch, and then committed or rolled back.
+    /// Stats are only updated, and a first event hook only invoked, when the transaction is committed,
+    /// but a line inspector sees the lines of a rolled back transaction again when they are decoded again.
+    /// If decoding fails, the codec is restored and the error is returned.
+    pub fn decode_transaction<'a>(
+        &'a mut self,
+        bytes: &'a mut BytesMut,
+    ) -> Result<Transaction<'a>, SseCodecError> {
+        let snapshot = Snapshot::new(self);
+        let stats = self.parser.stats.take();
+        let first_event_hook = self.parser.first_event_hook.take();
+
+        let mut consumed = 0;
+        let mut events = Vec::new();
+        let result = loop {
+            match self.decode_slice(&bytes[consumed..]) {
+                Ok((Some(event), n)) => {
+                    consumed += n;
+                    events.push(event);
+                }
+                Ok((None, n)) => {
+                    // Complete lines of a partial event are consumed too, like with decode.
+                    consumed += n;
+                    break Ok(());
+                }
+                Err(error) => break Err(error),
+            }
+        };
+
+        self.parser.stats = stats;
+        self.parser.first_event_hook = first_event_hook;
+        if let Err(error) = result {
+            snapshot.restore(self);
+            return Err(error);
+        }
+
+        Ok(Transaction {
+            codec: self,
+            bytes,
+            snapshot: Some(snapshot),
+            consumed,
+            events,
+        })
+    }
+}
+
+impl Transaction<'_> {
+    /// Get the decoded events.
+    pub fn events(&self) -> &[SseEvent] {
+        &self.events
+    }
+
+    /// Advance the buffer past the decoded lines, and return the events.
+    pub fn commit(mut self) -> Vec<SseEvent> {
+        self.snapshot = None;
+        self.bytes.advance(self.consumed);
+        if let Some(stats) = self.codec.parser.stats.as_ref() {
+            stats.add_bytes(self.consumed);
+            for _ in 0..self.events.len() {
+                stats.add_event();
+            }
+        }
+        if !self.events.is_empty() {
+            if let Some(first_event_hook) = self.codec.parser.first_event_hook.take() {
+                first_event_hook.fire();
+            }
+        }
+
+        std::mem::take(&mut self.events)
+    }
+
+    /// Restore the codec, leaving the buffer unchanged.
+    ///
+    /// This is the same as dropping the transaction.
+    pub fn rollback(self) {}
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            snapshot.restore(self.codec);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::LineEnding;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use tokio_util::codec::Decoder;
+
+    #[test]
+    fn rollback() {
+        let test_data = "id: 1\ndata: a\n\nid: 2\ndata: b\n\nid: 3\ndata: partial";
+        let mut codec = SseCodec::new();
+        let mut buffer = BytesMut::from(test_data);
+
+        let transaction = codec
+            .decode_transaction(&mut buffer)
+            .expect("failed to parse");
+        let valid = transaction
+            .events()
+            .iter()
+            .all(|event| event.data.as_deref() == Some("a"));
+        assert!(transaction.events().len() == 2);
+        assert!(!valid);
+        transaction.rollback();
+
+        assert!(&buffer[..] == test_data.as_bytes());
+        assert!(codec.last_event_id().is_none());
+
+        // Decoding again produces the same events.
+        let events = codec
+            .decode_transaction(&mut buffer)
+            .expect("failed to parse")
+            .commit();
+        assert!(
+            events
+                == [
+                    SseEvent::new().with_id("1").with_data("a"),
+                    SseEvent::new().with_id("2").with_data("b")
+                ]
+        );
+        assert!(&buffer[..] == b"data: partial");
+        assert!(codec.last_event_id() == Some("2"));
+
+        buffer.extend_from_slice(b"\n\n");
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event == SseEvent::new().with_id("3").with_data("partial"));
+    }
+
+    #[test]
+    fn rollback_first_event_hook() {
+        let fired = Arc::new(AtomicUsize::new(0));
+        let mut codec = SseCodec::new().with_first_event_hook({
+            let fired = fired.clone();
+            move || {
+                fired.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        let mut buffer = BytesMut::from("data: a\n\ndata: b\n\n");
+
+        codec
+            .decode_transaction(&mut buffer)
+            .expect("failed to parse")
+            .rollback();
+        assert!(fired.load(Ordering::SeqCst) == 0);
+
+        // The hook is still installed, and fires once the events are committed.
+        let events = codec
+            .decode_transaction(&mut buffer)
+            .expect("failed to parse")
+            .commit();
+        assert!(events.len() == 2);
+        assert!(fired.load(Ordering::SeqCst) == 1);
+
+        buffer.extend_from_slice(b"data: c\n\n");
+        codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(fired.load(Ordering::SeqCst) == 1);
+    }
+
+    #[test]
+    fn rollback_on_error() {
+        let mut codec = SseCodec::new().with_stats();
+        let stats = codec.stats_handle().expect("missing stats");
+        let mut buffer = BytesMut::from(&b"data: a\n\ndata: \xFF\n\n"[..]);
+
+        let error = codec
+            .decode_transaction(&mut buffer)
+            .expect_err("missing error");
+        assert!(matches!(error, SseCodecError::InvalidUtf8 { line: 3, .. }));
+        assert!(buffer.len() == 18);
+        assert!(stats.bytes() == 0);
+        assert!(stats.events() == 0);
+    }
+
+    #[test]
+    fn rollback_line_ending_inspector() {
+        let line_endings = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut codec = SseCodec::new().with_line_ending_inspector({
+            let line_endings = line_endings.clone();
+            move |line_ending| line_endings.lock().unwrap().push(line_ending)
+        });
+
+        // The buffer ends with a \r, so its line ending is not known yet.
+        let mut buffer = BytesMut::from("data: a\r");
+        assert!(codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .is_none());
+        assert!(line_endings.lock().unwrap().is_empty());
+
+        buffer.extend_from_slice(b"\n\n");
+        let transaction = codec
+            .decode_transaction(&mut buffer)
+            .expect("failed to parse");
+        assert!(transaction.events().len() == 1);
+        transaction.rollback();
+        assert!(*line_endings.lock().unwrap() == [LineEnding::CrLf, LineEnding::Lf]);
+
+        // The pending \r is restored, so it is reported again.
+        let event = codec
+            .decode(&mut buffer)
+            .expect("failed to parse")
+            .expect("missing event");
+        assert!(event == SseEvent::new().with_data("a"));
+        assert!(
+            *line_endings.lock().unwrap()
+                == [
+                    LineEnding::CrLf,
+                    LineEnding::Lf,
+                    LineEnding::CrLf,
+                    LineEnding::Lf
+                ]
+        );
+    }
+}
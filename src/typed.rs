@@ -0,0 +1,157 @@
+use crate::SseCodecError;
+use crate::SseEvent;
+use std::convert::Infallible;
+
+/// A type that can be built from a dispatched event
+///
+/// With the `derive` feature, this can be derived for enums,
+/// matching each variant by event type with `#[sse(event = "...")]`.
+/// Events without an event type have the type `message`, per spec.
+///
+/// # Example
+/// ```
+/// # #[cfg(all(feature = "derive", feature = "json"))]
+/// # {
+/// use nd_tokio_sse_codec::FromSseEvent;
+/// use nd_tokio_sse_codec::Json;
+/// use nd_tokio_sse_codec::SseEvent;
+/// use std::collections::HashMap;
+///
+/// #[derive(FromSseEvent)]
+/// enum Message {
+///     #[sse(event = "price")]
+///     Price(Json<HashMap<String, u64>>),
+///
+///     #[sse(event = "message")]
+///     Text(String),
+///
+///     #[sse(event = "ping")]
+///     Ping,
+/// }
+///
+/// let event = SseEvent::new()
+///     .with_event("price")
+///     .with_data(r#"{"btc": 6500000}"#);
+/// let message = Message::from_sse_event(event).expect("failed to convert");
+/// assert!(matches!(message, Message::Price(Json(prices)) if prices["btc"] == 6500000));
+/// # }
+/// ```
+pub trait FromSseEvent: Sized {
+    /// Build a value from an event.
+    fn from_sse_event(event: SseEvent) -> Result<Self, SseCodecError>;
+}
+
+/// A type that can be parsed from the data field of an event
+///
+/// Events without data are treated as having empty data.
+pub trait FromSseData: Sized {
+    /// The parse error.
+    type Error: Into<Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Parse a value from the data field.
+    fn from_sse_data(data: &str) -> Result<Self, Self::Error>;
+}
+
+impl FromSseData for String {
+    type Error = Infallible;
+
+    fn from_sse_data(data: &str) -> Result<Self, Self::Error> {
+        Ok(data.to_string())
+    }
+}
+
+/// Data parsed as json
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Json<T>(pub T);
+
+#[cfg(feature = "json")]
+impl<T> FromSseData for Json<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    type Error = serde_json::Error;
+
+    fn from_sse_data(data: &str) -> Result<Self, Self::Error> {
+        serde_json::from_str(data).map(Json)
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod test {
+    use super::*;
+
+    /// A price, in cents.
+    #[derive(Debug, PartialEq)]
+    struct Cents(u64);
+
+    impl FromSseData for Cents {
+        type Error = std::num::ParseIntError;
+
+        fn from_sse_data(data: &str) -> Result<Self, Self::Error> {
+            data.parse().map(Cents)
+        }
+    }
+
+    #[derive(Debug, PartialEq, crate::FromSseEvent)]
+    enum Message {
+        #[sse(event = "price")]
+        Price(Cents),
+
+        #[sse(event = "message")]
+        Text(String),
+
+        #[sse(event = "ping")]
+        Ping,
+    }
+
+    #[test]
+    fn derive() {
+        let message = Message::from_sse_event(SseEvent::new().with_event("price").with_data("125"))
+            .expect("failed to convert");
+        assert!(message == Message::Price(Cents(125)));
+
+        let message =
+            Message::from_sse_event(SseEvent::new().with_data("hello")).expect("failed to convert");
+        assert!(message == Message::Text("hello".into()));
+
+        let message = Message::from_sse_event(SseEvent::new().with_event("ping").with_data("x"))
+            .expect("failed to convert");
+        assert!(message == Message::Ping);
+
+        let error = Message::from_sse_event(SseEvent::new().with_event("price").with_data("abc"))
+            .expect_err("missing error");
+        assert!(matches!(error, SseCodecError::InvalidEventData { event, .. } if event == "price"));
+
+        let error = Message::from_sse_event(SseEvent::new().with_event("trade").with_data("1"))
+            .expect_err("missing error");
+        assert!(matches!(error, SseCodecError::UnknownEvent { event } if event == "trade"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn derive_json() {
+        use std::collections::HashMap;
+
+        #[derive(Debug, PartialEq, crate::FromSseEvent)]
+        enum JsonMessage {
+            #[sse(event = "price")]
+            Price(Json<HashMap<String, u64>>),
+        }
+
+        let event = SseEvent::new()
+            .with_event("price")
+            .with_data(r#"{"btc": 6500000, "eth": 310000}"#);
+        let message = JsonMessage::from_sse_event(event).expect("failed to convert");
+        let prices = HashMap::from([("btc".to_string(), 6500000), ("eth".to_string(), 310000)]);
+        assert!(message == JsonMessage::Price(Json(prices)));
+
+        let event = SseEvent::new().with_event("price").with_data(r#"{"btc": "#);
+        let error = JsonMessage::from_sse_event(event).expect_err("missing error");
+        assert!(matches!(
+            &error,
+            SseCodecError::InvalidEventData { event, source }
+                if event == "price" && source.is::<serde_json::Error>()
+        ));
+    }
+}
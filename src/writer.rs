@@ -0,0 +1,163 @@
+use crate::encoder::encode_event;
+use crate::LineEnding;
+use crate::SseCodecError;
+use crate::SseEvent;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio_util::bytes::BytesMut;
+
+/// A writer for serving sse events.
+///
+/// Proxies and other intermediaries may buffer a response until it is flushed,
+/// so events sent with [`SseWriter::send_event`] may not reach the client promptly.
+/// Use [`SseWriter::send_event_flush`] to flush after each event,
+/// or send a batch of events and then call [`SseWriter::flush`].
+/// Flushing after each event minimizes latency, while batching reduces the number of writes.
+#[derive(Debug)]
+pub struct SseWriter<W> {
+    writer: W,
+
+    /// The newline written after each line.
+    line_ending: LineEnding,
+
+    /// The buffer for encoding events.
+    buffer: BytesMut,
+}
+
+impl<W> SseWriter<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    /// Make a new sse writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            line_ending: LineEnding::Lf,
+            buffer: BytesMut::new(),
+        }
+    }
+
+    /// Set the newline written after each line.
+    ///
+    /// Some legacy clients require `\r\n`.
+    /// By default, this is [`LineEnding::Lf`].
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Write an event, without flushing.
+    pub async fn send_event(&mut self, event: &SseEvent) -> Result<(), SseCodecError> {
+        self.buffer.clear();
+        encode_event(event, self.line_ending, &mut self.buffer)?;
+        self.writer.write_all(&self.buffer).await?;
+
+        Ok(())
+    }
+
+    /// Write an event, and then flush the writer.
+    pub async fn send_event_flush(&mut self, event: &SseEvent) -> Result<(), SseCodecError> {
+        self.send_event(event).await?;
+        self.flush().await
+    }
+
+    /// Flush the writer.
+    pub async fn flush(&mut self) -> Result<(), SseCodecError> {
+        self.writer.flush().await?;
+
+        Ok(())
+    }
+
+    /// Get the inner writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::pin::Pin;
+    use std::task::Context;
+    use std::task::Poll;
+
+    /// A writer that records the bytes written at each flush.
+    #[derive(Default)]
+    struct FlushRecorder {
+        buffer: Vec<u8>,
+        flushes: Vec<Vec<u8>>,
+    }
+
+    impl AsyncWrite for FlushRecorder {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.buffer.extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            let flushed = std::mem::take(&mut self.buffer);
+            self.flushes.push(flushed);
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn flush_per_event() {
+        let mut writer = SseWriter::new(FlushRecorder::default());
+        writer
+            .send_event_flush(&SseEvent::new().with_data("a"))
+            .await
+            .expect("failed to send");
+        writer
+            .send_event_flush(&SseEvent::new().with_data("b"))
+            .await
+            .expect("failed to send");
+
+        let recorder = writer.into_inner();
+        assert!(recorder.flushes == [b"data: a\n\n".to_vec(), b"data: b\n\n".to_vec()]);
+        assert!(recorder.buffer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn batched_flush() {
+        let mut writer = SseWriter::new(FlushRecorder::default());
+        writer
+            .send_event(&SseEvent::new().with_data("a"))
+            .await
+            .expect("failed to send");
+        writer
+            .send_event(&SseEvent::new().with_data("b"))
+            .await
+            .expect("failed to send");
+
+        assert!(writer.writer.flushes.is_empty());
+
+        writer.flush().await.expect("failed to flush");
+        let recorder = writer.into_inner();
+        assert!(recorder.flushes == [b"data: a\n\ndata: b\n\n".to_vec()]);
+    }
+
+    #[tokio::test]
+    async fn line_ending() {
+        let mut writer =
+            SseWriter::new(FlushRecorder::default()).with_line_ending(LineEnding::CrLf);
+        writer
+            .send_event_flush(&SseEvent::new().with_event("a").with_data("b"))
+            .await
+            .expect("failed to send");
+
+        let recorder = writer.into_inner();
+        assert!(recorder.flushes == [b"event: a\r\ndata: b\r\n\r\n".to_vec()]);
+    }
+}